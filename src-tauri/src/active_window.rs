@@ -0,0 +1,65 @@
+//! Application that has the keyboard focus when a dictation starts.
+//! Only X11 (including XWayland windows) is supported for now.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActiveWindow {
+    /// Native window id
+    pub id: u64,
+    /// WM_CLASS class, e.g. "Slack"
+    pub class: String,
+    /// WM_CLASS instance, e.g. "slack"
+    pub instance: String,
+}
+
+impl ActiveWindow {
+    /// Whether `name` is this window's class or instance, ignoring case.
+    pub fn matches(&self, name: &str) -> bool {
+        self.class.eq_ignore_ascii_case(name) || self.instance.eq_ignore_ascii_case(name)
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn detect() -> Option<ActiveWindow> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+
+    let active_atom = conn
+        .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let active = conn
+        .get_property(false, root, active_atom, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    let window = active.value32()?.next().filter(|&w| w != 0)?;
+
+    // WM_CLASS holds two NUL-terminated strings: instance then class
+    let wm_class = conn
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+        .ok()?
+        .reply()
+        .ok()?;
+    let mut parts = wm_class
+        .value
+        .split(|&b| b == 0)
+        .map(|part| String::from_utf8_lossy(part).into_owned());
+
+    Some(ActiveWindow {
+        id: window as u64,
+        instance: parts.next().unwrap_or_default(),
+        class: parts.next().unwrap_or_default(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn detect() -> Option<ActiveWindow> {
+    None
+}
@@ -74,15 +74,7 @@ pub fn start_recording(
             &stream_config,
             move |data: &[f32], _: &cpal::InputCallbackInfo| {
                 let mut buf = buf_writer.lock().unwrap();
-                if channels == 1 {
-                    buf.extend_from_slice(data);
-                } else {
-                    // Mix multi-channel down to mono
-                    for chunk in data.chunks(channels) {
-                        let mono: f32 = chunk.iter().sum::<f32>() / channels as f32;
-                        buf.push(mono);
-                    }
-                }
+                mix_to_mono(data, channels, &mut buf);
             },
             |err| log::error!("Erreur flux audio : {}", err),
             None,
@@ -186,14 +178,7 @@ pub fn test_microphone(app: AppHandle, device_name: Option<String>) -> AppResult
             &stream_config,
             move |data: &[f32], _: &cpal::InputCallbackInfo| {
                 let mut buf = buf_writer.lock().unwrap();
-                if channels == 1 {
-                    buf.extend_from_slice(data);
-                } else {
-                    for chunk in data.chunks(channels) {
-                        let mono: f32 = chunk.iter().sum::<f32>() / channels as f32;
-                        buf.push(mono);
-                    }
-                }
+                mix_to_mono(data, channels, &mut buf);
             },
             |err| log::error!("Erreur flux audio (test micro) : {}", err),
             None,
@@ -229,8 +214,20 @@ pub fn test_microphone(app: AppHandle, device_name: Option<String>) -> AppResult
     Ok(())
 }
 
+/// Append interleaved `data` to `out`, mixing multi-channel frames down to mono.
+pub fn mix_to_mono(data: &[f32], channels: usize, out: &mut Vec<f32>) {
+    if channels <= 1 {
+        out.extend_from_slice(data);
+    } else {
+        for chunk in data.chunks(channels) {
+            let mono: f32 = chunk.iter().sum::<f32>() / channels as f32;
+            out.push(mono);
+        }
+    }
+}
+
 /// Linear interpolation resampler.
-fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+pub fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     if input.is_empty() {
         return Vec::new();
     }
@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use crate::audio;
+use crate::errors::{AppError, AppResult};
+
+/// File extensions accepted by `decode_file`.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "flac", "mp3", "ogg", "oga"];
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SUPPORTED_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Decode an audio file (WAV, FLAC, MP3, OGG/Vorbis) into 16 kHz mono samples,
+/// ready to be fed to Whisper.
+pub fn decode_file(path: &Path) -> AppResult<Vec<f32>> {
+    let file = File::open(path)
+        .map_err(|e| AppError::Audio(format!("Ouverture de {} impossible : {}", path.display(), e)))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| AppError::Audio(format!("Format audio non reconnu : {}", e)))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| AppError::Audio("Aucune piste audio dans le fichier".into()))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| AppError::Audio(format!("Codec non supporté : {}", e)))?;
+
+    let mut mono: Vec<f32> = Vec::new();
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(p) => p,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(AppError::Audio(format!("Lecture du fichier impossible : {}", e))),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                sample_rate = spec.rate;
+
+                let mut buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buf.copy_interleaved_ref(decoded);
+                audio::mix_to_mono(buf.samples(), spec.channels.count(), &mut mono);
+            }
+            // Corrupted frames are skipped rather than aborting the whole file
+            Err(SymphoniaError::DecodeError(e)) => {
+                log::warn!("Trame audio ignorée : {}", e);
+            }
+            Err(e) => return Err(AppError::Audio(format!("Décodage impossible : {}", e))),
+        }
+    }
+
+    if sample_rate == 0 {
+        return Err(AppError::Audio("Fréquence d'échantillonnage inconnue".into()));
+    }
+
+    log::info!(
+        "Fichier décodé : {} ({} échantillons à {} Hz)",
+        path.display(),
+        mono.len(),
+        sample_rate
+    );
+
+    if sample_rate != 16000 && !mono.is_empty() {
+        Ok(audio::resample(&mono, sample_rate, 16000))
+    } else {
+        Ok(mono)
+    }
+}
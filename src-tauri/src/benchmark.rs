@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio_file;
+use crate::errors::{AppError, AppResult};
+use crate::models;
+use crate::transcription::{self, TranscribeOptions};
+
+const RESULTS_FILE: &str = "benchmarks.json";
+const CLIP_RESOURCE: &str = "resources/benchmark/reference.wav";
+const SAMPLE_RATE: usize = 16000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub model_id: String,
+    pub load_ms: u64,
+    pub transcribe_ms: u64,
+    /// Processing time divided by clip duration: below 1.0 is faster than real time
+    pub real_time_factor: f64,
+    /// Process memory growth while the model was loaded and running
+    pub peak_memory_mb: f64,
+    /// Backend the model ran on (`force_cpu` applied)
+    pub backend: String,
+    pub measured_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkProgress {
+    pub model_id: String,
+    pub index: usize,
+    pub total: usize,
+}
+
+/// Reference clip bundled with the app (see `resources/benchmark/README.md`):
+/// real English speech, so decoding produces as many tokens as dictation does.
+fn reference_clip(app: &AppHandle) -> AppResult<Vec<f32>> {
+    let path = app
+        .path()
+        .resolve(CLIP_RESOURCE, BaseDirectory::Resource)
+        .map_err(|e| AppError::Transcription(format!("Extrait de référence introuvable : {}", e)))?;
+    if !path.exists() {
+        return Err(AppError::Transcription(format!(
+            "Extrait de référence introuvable : {}",
+            path.display()
+        )));
+    }
+    audio_file::decode_file(&path)
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn process_memory(sys: &mut System, pid: Pid) -> u64 {
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]));
+    sys.process(pid).map(|p| p.memory()).unwrap_or(0)
+}
+
+/// Samples the process RSS every 50 ms until dropped, keeping the maximum.
+struct MemorySampler {
+    peak: Arc<AtomicU64>,
+    running: Arc<AtomicBool>,
+}
+
+impl MemorySampler {
+    fn start(pid: Pid) -> Self {
+        let peak = Arc::new(AtomicU64::new(0));
+        let running = Arc::new(AtomicBool::new(true));
+        let (p, r) = (peak.clone(), running.clone());
+        std::thread::spawn(move || {
+            let mut sys = System::new();
+            while r.load(Ordering::SeqCst) {
+                p.fetch_max(process_memory(&mut sys, pid), Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+        });
+        Self { peak, running }
+    }
+
+    fn peak(&self) -> u64 {
+        self.peak.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for MemorySampler {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+fn benchmark_model(
+    path: &Path,
+    model_id: &str,
+    force_cpu: bool,
+    clip: &[f32],
+) -> AppResult<BenchmarkResult> {
+    let pid = sysinfo::get_current_pid()
+        .map_err(|e| AppError::Transcription(format!("PID introuvable : {}", e)))?;
+    let mut sys = System::new();
+    let baseline = process_memory(&mut sys, pid);
+    let sampler = MemorySampler::start(pid);
+
+    let started = Instant::now();
+    let engine = transcription::load_model(path, force_cpu)?;
+    let load_ms = started.elapsed().as_millis() as u64;
+
+    // The clip is English: a fixed language keeps multilingual models from
+    // spending a detection pass the English-only ones skip
+    let options = TranscribeOptions {
+        language: "en".to_string(),
+        timestamps: false,
+        diarize: false,
+    };
+    let started = Instant::now();
+    engine.transcribe(clip, &options, None)?;
+    let transcribe_ms = started.elapsed().as_millis() as u64;
+
+    let peak = sampler.peak().max(process_memory(&mut sys, pid));
+    drop(sampler);
+    drop(engine);
+
+    Ok(BenchmarkResult {
+        model_id: model_id.to_string(),
+        load_ms,
+        transcribe_ms,
+        real_time_factor: transcribe_ms as f64 * SAMPLE_RATE as f64 / (clip.len() as f64 * 1000.0),
+        peak_memory_mb: peak.saturating_sub(baseline) as f64 / 1_048_576.0,
+        backend: transcription::active_backend(force_cpu).to_string(),
+        measured_at: unix_secs(),
+    })
+}
+
+pub fn load_results(app_data_dir: &Path) -> Vec<BenchmarkResult> {
+    std::fs::read_to_string(app_data_dir.join(RESULTS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_results(app_data_dir: &Path, results: &[BenchmarkResult]) -> AppResult<()> {
+    let json = serde_json::to_string_pretty(results)
+        .map_err(|e| AppError::Config(format!("Sérialisation impossible : {}", e)))?;
+    std::fs::write(app_data_dir.join(RESULTS_FILE), json)?;
+    Ok(())
+}
+
+/// Benchmark every downloaded model on the reference clip and store the
+/// results. Blocking: models are loaded one at a time.
+pub fn run(
+    app: &AppHandle,
+    app_data_dir: &Path,
+    force_cpu: bool,
+) -> AppResult<Vec<BenchmarkResult>> {
+    let dir = app_data_dir.to_path_buf();
+    let downloaded: Vec<String> = models::list_models(&dir)
+        .into_iter()
+        .filter(|m| m.is_downloaded)
+        .map(|m| m.id)
+        .collect();
+
+    if downloaded.is_empty() {
+        return Err(AppError::Transcription(
+            "Aucun modèle installé. Veuillez télécharger un modèle dans les paramètres.".into(),
+        ));
+    }
+
+    let clip = reference_clip(app)?;
+    let mut results = load_results(app_data_dir);
+
+    for (index, model_id) in downloaded.iter().enumerate() {
+        let _ = app.emit(
+            "benchmark-progress",
+            BenchmarkProgress {
+                model_id: model_id.clone(),
+                index,
+                total: downloaded.len(),
+            },
+        );
+
+        let Some(path) = models::get_model_path(&dir, model_id) else {
+            continue;
+        };
+
+        match benchmark_model(&path, model_id, force_cpu, &clip) {
+            Ok(result) => {
+                log::info!(
+                    "Benchmark {} : chargement {} ms, RTF {:.2}, mémoire {:.0} Mo",
+                    model_id,
+                    result.load_ms,
+                    result.real_time_factor,
+                    result.peak_memory_mb
+                );
+                results.retain(|r| r.model_id != result.model_id);
+                results.push(result);
+            }
+            Err(e) => {
+                log::error!("Benchmark {} impossible : {}", model_id, e);
+                let _ = app.emit(
+                    "error",
+                    format!("Benchmark {} impossible : {}", model_id, e),
+                );
+            }
+        }
+    }
+
+    save_results(app_data_dir, &results)?;
+    Ok(results)
+}
@@ -0,0 +1,342 @@
+//! Key chords such as "Ctrl+Shift+V". A letter, digit or punctuation key
+//! stands for the key that types that character in the active layout, so
+//! "Ctrl+V" pastes on AZERTY and Dvorak too. On X11 the keycode is looked up
+//! in the server's keymap, falling back to enigo's binding of a spare keycode;
+//! macOS and Windows resolve it through the system layout. Backends that
+//! cannot see the layout (ydotool, uinput) use the key's position on a US
+//! QWERTY keyboard.
+
+use enigo::{Direction, Enigo, Key, Keyboard};
+
+use crate::errors::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    /// Cmd on macOS, Super/Windows elsewhere
+    Meta,
+}
+
+impl Modifier {
+    /// Linux evdev code of the left-hand key.
+    pub fn evdev_code(self) -> u16 {
+        match self {
+            Modifier::Ctrl => 29,
+            Modifier::Shift => 42,
+            Modifier::Alt => 56,
+            Modifier::Meta => 125,
+        }
+    }
+
+    fn key(self) -> Key {
+        match self {
+            Modifier::Ctrl => Key::Control,
+            Modifier::Shift => Key::Shift,
+            Modifier::Alt => Key::Alt,
+            Modifier::Meta => Key::Meta,
+        }
+    }
+}
+
+/// Non-modifier keys a chord can end with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordKey {
+    /// Key typing this character: a lowercase letter, a digit or punctuation
+    Char(char),
+    Insert,
+    Delete,
+    Backspace,
+    Enter,
+    Tab,
+    Space,
+}
+
+impl ChordKey {
+    /// Linux evdev code on a US QWERTY keyboard, for backends that cannot
+    /// read the layout. `None` for characters that need Shift or are absent
+    /// from that layout.
+    pub fn qwerty_evdev_code(self) -> Option<u16> {
+        const LETTERS: [u16; 26] = [
+            30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, 49, 24, 25, 16, 19, 31, 20, 22, 47,
+            17, 45, 21, 44,
+        ];
+        Some(match self {
+            ChordKey::Char(c @ 'a'..='z') => LETTERS[(c as u8 - b'a') as usize],
+            ChordKey::Char('0') => 11,
+            ChordKey::Char(c @ '1'..='9') => 1 + (c as u8 - b'0') as u16,
+            ChordKey::Char('-') => 12,
+            ChordKey::Char('=') => 13,
+            ChordKey::Char('[') => 26,
+            ChordKey::Char(']') => 27,
+            ChordKey::Char(';') => 39,
+            ChordKey::Char('\'') => 40,
+            ChordKey::Char('`') => 41,
+            ChordKey::Char('\\') => 43,
+            ChordKey::Char(',') => 51,
+            ChordKey::Char('.') => 52,
+            ChordKey::Char('/') => 53,
+            ChordKey::Char(_) => return None,
+            ChordKey::Insert => 110,
+            ChordKey::Delete => 111,
+            ChordKey::Backspace => 14,
+            ChordKey::Enter => 28,
+            ChordKey::Tab => 15,
+            ChordKey::Space => 57,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    pub modifiers: Vec<Modifier>,
+    pub key: ChordKey,
+}
+
+impl Chord {
+    /// Evdev codes of the modifiers then the key, on a US QWERTY keyboard.
+    pub fn qwerty_evdev_codes(&self) -> AppResult<Vec<u16>> {
+        let key = self.key.qwerty_evdev_code().ok_or_else(|| {
+            AppError::Clipboard(format!("Touche {:?} absente d'un clavier QWERTY sans Maj", self.key))
+        })?;
+        Ok(self.modifiers.iter().map(|m| m.evdev_code()).chain([key]).collect())
+    }
+}
+
+fn parse_modifier(name: &str) -> Option<Modifier> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "ctrl" | "control" => Modifier::Ctrl,
+        "shift" => Modifier::Shift,
+        "alt" | "option" => Modifier::Alt,
+        "super" | "meta" | "cmd" | "command" | "win" => Modifier::Meta,
+        "cmdorctrl" if cfg!(target_os = "macos") => Modifier::Meta,
+        "cmdorctrl" => Modifier::Ctrl,
+        _ => return None,
+    })
+}
+
+fn parse_key(name: &str) -> Option<ChordKey> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return (!c.is_whitespace() && !c.is_control())
+            .then(|| ChordKey::Char(c.to_lowercase().next().unwrap_or(c)));
+    }
+    Some(match name.to_ascii_lowercase().as_str() {
+        "insert" | "ins" => ChordKey::Insert,
+        "delete" | "del" => ChordKey::Delete,
+        "backspace" => ChordKey::Backspace,
+        "enter" | "return" => ChordKey::Enter,
+        "tab" => ChordKey::Tab,
+        "space" => ChordKey::Space,
+        // "+" separates the keys of the chord
+        "plus" => ChordKey::Char('+'),
+        _ => return None,
+    })
+}
+
+/// Parse "Ctrl+Shift+V": any number of modifiers, then exactly one key.
+pub fn parse(chord: &str) -> AppResult<Chord> {
+    let invalid = |reason: &str| AppError::Config(format!("Raccourci \"{}\" invalide : {}", chord, reason));
+
+    let parts: Vec<&str> = chord.split('+').map(str::trim).collect();
+    let (key, modifiers) = parts.split_last().ok_or_else(|| invalid("vide"))?;
+
+    let key = parse_key(key).ok_or_else(|| invalid("touche inconnue"))?;
+    let modifiers = modifiers
+        .iter()
+        .map(|m| parse_modifier(m).ok_or_else(|| invalid("modificateur inconnu")))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    Ok(Chord { modifiers, key })
+}
+
+/// How `send` presses the chord's key.
+enum Stroke {
+    /// Platform keycode, with Shift when the character is on the shifted level
+    Raw { code: u16, shift: bool },
+    /// Left to enigo, which resolves characters through the system layout
+    Key(Key),
+}
+
+#[cfg(target_os = "linux")]
+fn stroke(key: ChordKey) -> Option<Stroke> {
+    // X11 keycodes are evdev codes plus 8
+    let raw = |evdev: u16| Stroke::Raw {
+        code: evdev + 8,
+        shift: false,
+    };
+    match key {
+        ChordKey::Char(c) => match x11_keymap::keycode(c) {
+            Some((code, shift)) => Some(Stroke::Raw {
+                code: code.into(),
+                shift,
+            }),
+            // enigo binds the character to a spare keycode
+            None => Some(Stroke::Key(Key::Unicode(c))),
+        },
+        _ => key.qwerty_evdev_code().map(raw),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn stroke(key: ChordKey) -> Option<Stroke> {
+    let raw = |code: u16| Stroke::Raw { code, shift: false };
+    match key {
+        ChordKey::Char(c) => Some(Stroke::Key(Key::Unicode(c))),
+        // enigo sets the extended-key flag for codes above 0x7F
+        ChordKey::Insert => Some(raw(0x52 | 0x80)),
+        ChordKey::Delete => Some(raw(0x53 | 0x80)),
+        // Scancodes equal evdev codes for the remaining keys
+        _ => key.qwerty_evdev_code().map(raw),
+    }
+}
+
+/// macOS virtual keycodes (kVK_*) for the named keys.
+#[cfg(target_os = "macos")]
+fn stroke(key: ChordKey) -> Option<Stroke> {
+    let code = match key {
+        ChordKey::Char(c) => return Some(Stroke::Key(Key::Unicode(c))),
+        // Help sits where Insert is on a PC keyboard
+        ChordKey::Insert => 0x72,
+        ChordKey::Delete => 0x75,
+        ChordKey::Backspace => 0x33,
+        ChordKey::Enter => 0x24,
+        ChordKey::Tab => 0x30,
+        ChordKey::Space => 0x31,
+    };
+    Some(Stroke::Raw { code, shift: false })
+}
+
+/// Keycode lookup in the X server's keymap, for the active layout group.
+#[cfg(target_os = "linux")]
+mod x11_keymap {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xkb::{self, ConnectionExt as _};
+    use x11rb::protocol::xproto::ConnectionExt as _;
+    use x11rb::rust_connection::RustConnection;
+
+    /// X keysym of a character: Latin-1 code points are their own keysym,
+    /// the rest of Unicode is offset by 0x0100_0000.
+    fn keysym(c: char) -> u32 {
+        match c as u32 {
+            cp @ (0x20..=0x7e | 0xa0..=0xff) => cp,
+            cp => 0x0100_0000 + cp,
+        }
+    }
+
+    /// Layout group in use, counted from 0.
+    fn active_group(conn: &RustConnection) -> Option<usize> {
+        conn.xkb_use_extension(1, 0).ok()?.reply().ok()?;
+        let state = conn
+            .xkb_get_state(xkb::ID::USE_CORE_KBD.into())
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(u8::from(state.group).into())
+    }
+
+    /// Keycode typing `c` and whether it needs Shift. `None` without an X
+    /// server or when no key of the layout types `c`.
+    pub fn keycode(c: char) -> Option<(u8, bool)> {
+        let (conn, _) = x11rb::connect(None).ok()?;
+        let (min, max) = (conn.setup().min_keycode, conn.setup().max_keycode);
+        let mapping = conn
+            .get_keyboard_mapping(min, max - min + 1)
+            .ok()?
+            .reply()
+            .ok()?;
+        let per_keycode = usize::from(mapping.keysyms_per_keycode);
+        let target = keysym(c);
+
+        // The core map lists group 1 levels 1-2, then group 2 levels 1-2
+        let find = |group: usize| {
+            (0..2).find_map(|level| {
+                let column = group * 2 + level;
+                if column >= per_keycode {
+                    return None;
+                }
+                mapping
+                    .keysyms
+                    .chunks(per_keycode)
+                    .position(|syms| syms[column] == target)
+                    .map(|i| (min + i as u8, level == 1))
+            })
+        };
+
+        // Shortcuts of a non-Latin group resolve through the first group, as
+        // toolkits do
+        let group = active_group(&conn).unwrap_or(0).min(1);
+        find(group).or_else(|| find(0))
+    }
+}
+
+/// Press the chord. Modifiers are released even if a press fails, so none
+/// stays stuck; the first error is returned afterwards.
+pub fn send(enigo: &mut Enigo, chord: &Chord) -> AppResult<()> {
+    let failed = |e: enigo::InputError| AppError::Clipboard(format!("Simulation clavier échouée : {}", e));
+
+    let stroke = stroke(chord.key).ok_or_else(|| {
+        AppError::Clipboard(format!("Touche {:?} introuvable sur ce clavier", chord.key))
+    })?;
+    let mut modifiers = chord.modifiers.clone();
+    if matches!(stroke, Stroke::Raw { shift: true, .. }) && !modifiers.contains(&Modifier::Shift) {
+        modifiers.push(Modifier::Shift);
+    }
+
+    let mut pressed = Vec::with_capacity(modifiers.len());
+    let mut result = Ok(());
+    for modifier in modifiers {
+        match enigo.key(modifier.key(), Direction::Press) {
+            Ok(()) => pressed.push(modifier),
+            Err(e) => {
+                result = Err(failed(e));
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        result = match stroke {
+            Stroke::Raw { code, .. } => enigo.raw(code, Direction::Click),
+            Stroke::Key(key) => enigo.key(key, Direction::Click),
+        }
+        .map_err(failed);
+    }
+    for modifier in pressed.into_iter().rev() {
+        let released = enigo.key(modifier.key(), Direction::Release).map_err(failed);
+        result = result.and(released);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_name_the_character_they_type() {
+        let chord = parse("Ctrl+Shift+V").unwrap();
+        assert_eq!(chord.modifiers, [Modifier::Ctrl, Modifier::Shift]);
+        assert_eq!(chord.key, ChordKey::Char('v'));
+        assert_eq!(parse("Ctrl+.").unwrap().key, ChordKey::Char('.'));
+        assert_eq!(parse("Alt+É").unwrap().key, ChordKey::Char('é'));
+        assert_eq!(parse("Ctrl+Plus").unwrap().key, ChordKey::Char('+'));
+        assert_eq!(parse("Shift+Insert").unwrap().key, ChordKey::Insert);
+    }
+
+    #[test]
+    fn malformed_chords_are_rejected() {
+        for chord in ["", "Ctrl+", "Ctrl++", "Ctrl+Hyper+V", "Ctrl+F13"] {
+            assert!(parse(chord).is_err(), "{:?} is rejected", chord);
+        }
+    }
+
+    #[test]
+    fn qwerty_fallback_covers_unshifted_keys() {
+        assert_eq!(ChordKey::Char('v').qwerty_evdev_code(), Some(47));
+        assert_eq!(ChordKey::Char('0').qwerty_evdev_code(), Some(11));
+        assert_eq!(ChordKey::Char('.').qwerty_evdev_code(), Some(52));
+        assert_eq!(ChordKey::Char('!').qwerty_evdev_code(), None);
+        assert_eq!(ChordKey::Char('é').qwerty_evdev_code(), None);
+    }
+}
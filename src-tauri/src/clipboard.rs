@@ -1,65 +1,79 @@
-use tauri::{AppHandle, Emitter};
+use std::time::Duration;
+use tauri::image::Image;
+use tauri::AppHandle;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
+use crate::chord;
+use crate::config::AppConfig;
 use crate::errors::{AppError, AppResult};
+use crate::injection::{self, Action};
 
-pub fn copy_and_paste(app: &AppHandle, text: &str, auto_paste: bool) -> AppResult<()> {
-    app.clipboard()
-        .write_text(text.to_string())
-        .map_err(|e| AppError::Clipboard(format!("Copie impossible : {}", e)))?;
-
-    if auto_paste {
-        // Small delay so the clipboard content is ready before the paste keystroke
-        std::thread::sleep(std::time::Duration::from_millis(100));
+/// What the user had on the clipboard before a dictation replaced it.
+enum Saved {
+    Text(String),
+    Image(Image<'static>),
+}
 
-        // enigo must run on the main thread on macOS (HIToolbox / TSM APIs require it).
-        // Use run_on_main_thread to dispatch the paste simulation safely.
-        let app_handle = app.clone();
-        app.run_on_main_thread(move || {
-            if let Err(e) = simulate_paste() {
-                log::error!("Simulation collage echouee : {}", e);
-                let _ = app_handle.emit("error", format!("Erreur collage : {}", e));
-            }
-        })
-        .map_err(|e| AppError::Clipboard(format!("Dispatch main thread impossible : {}", e)))?;
+/// Must not run on the main thread (see `read_text`).
+fn save_clipboard(app: &AppHandle) -> Option<Saved> {
+    let clipboard = app.clipboard();
+    if let Ok(text) = clipboard.read_text() {
+        return Some(Saved::Text(text));
     }
+    clipboard
+        .read_image()
+        .ok()
+        .map(|image| Saved::Image(image.to_owned()))
+}
 
-    Ok(())
+/// Put `saved` back after `delay`, unless the clipboard no longer holds the
+/// dictation because the user copied something else in the meantime.
+fn restore_later(app: &AppHandle, saved: Saved, dictation: String, delay: Duration) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+
+        let clipboard = app.clipboard();
+        if clipboard.read_text().ok().as_deref() != Some(dictation.as_str()) {
+            log::info!("Presse-papier modifié depuis la dictée, restauration annulée");
+            return;
+        }
+        let restored = match &saved {
+            Saved::Text(text) => clipboard.write_text(text.as_str()),
+            Saved::Image(image) => clipboard.write_image(image),
+        };
+        if let Err(e) = restored {
+            log::error!("Restauration du presse-papier impossible : {}", e);
+        }
+    });
 }
 
-fn simulate_paste() -> AppResult<()> {
-    use enigo::{
-        Direction::{Click, Press, Release},
-        Enigo, Key, Keyboard, Settings,
+pub fn copy_and_paste(app: &AppHandle, text: &str, config: &AppConfig) -> AppResult<()> {
+    let restore = &config.clipboard_restore;
+    // Only worth it when the text is pasted for the user; otherwise copying
+    // it is the point
+    let saved = if config.auto_paste && restore.enabled {
+        save_clipboard(app)
+    } else {
+        None
     };
 
-    let mut enigo = Enigo::new(&Settings::default())
-        .map_err(|e| AppError::Clipboard(format!("Initialisation Enigo impossible : {}", e)))?;
+    app.clipboard()
+        .write_text(text.to_string())
+        .map_err(|e| AppError::Clipboard(format!("Copie impossible : {}", e)))?;
+
+    if config.auto_paste {
+        let chord = chord::parse(&config.paste_chord)?;
+        // Small delay so the clipboard content is ready before the paste keystroke
+        std::thread::sleep(std::time::Duration::from_millis(100));
 
-    #[cfg(target_os = "macos")]
-    {
-        enigo
-            .key(Key::Meta, Press)
-            .map_err(|e| AppError::Clipboard(format!("Touche Meta press échouée : {}", e)))?;
-        enigo
-            .key(Key::Unicode('v'), Click)
-            .map_err(|e| AppError::Clipboard(format!("Touche V click échouée : {}", e)))?;
-        enigo
-            .key(Key::Meta, Release)
-            .map_err(|e| AppError::Clipboard(format!("Touche Meta release échouée : {}", e)))?;
-    }
+        // On failure the dictation stays on the clipboard for a manual paste
+        injection::inject(app, config.input_backend, &Action::Chord { chord: &chord, times: 1 })?;
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        enigo
-            .key(Key::Control, Press)
-            .map_err(|e| AppError::Clipboard(format!("Touche Ctrl press échouée : {}", e)))?;
-        enigo
-            .key(Key::Unicode('v'), Click)
-            .map_err(|e| AppError::Clipboard(format!("Touche V click échouée : {}", e)))?;
-        enigo
-            .key(Key::Control, Release)
-            .map_err(|e| AppError::Clipboard(format!("Touche Ctrl release échouée : {}", e)))?;
+        if let Some(saved) = saved {
+            let delay = Duration::from_millis(restore.delay_ms);
+            restore_later(app, saved, text.to_string(), delay);
+        }
     }
 
     Ok(())
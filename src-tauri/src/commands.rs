@@ -1,14 +1,33 @@
+use chrono::Local;
 use tauri::{AppHandle, State};
 
+use crate::active_window::ActiveWindow;
 use crate::audio::{self, AudioDevice};
-use crate::config::AppConfig;
+use crate::benchmark::{self, BenchmarkResult};
+use crate::chord;
+use crate::config::{
+    AppConfig, AppProfile, ClipboardRestoreConfig, ContinuationConfig, HookConfig, InputBackend, NormalizationConfig,
+    OutputMode, OutputSink, RedactionConfig, RewriteConfig, TypingConfig, WatchFolderConfig,
+};
 use crate::errors::AppResult;
+use crate::export::{self, ExportFormat};
+use crate::file_transcription;
+use crate::hallucination::{self, PhantomPhrases};
+use crate::hook::{self, HookContext};
 use crate::hotkey;
+use crate::injection::{self, InjectionStatus};
 use crate::models::{self, ModelInfo};
 use crate::permissions::{self, PermissionStatus};
+use crate::postprocess::{self, TextRule, TextRuleSets};
+use crate::redaction;
+use crate::rewrite;
+use crate::sinks::{self, SinkContext};
+use crate::snippets::{self, Snippet};
 use crate::state::AppState;
 use crate::system_info::{self, SystemInfo};
-use crate::transcription;
+use crate::transcription::{self, TranscriptionResult};
+use crate::undo;
+use crate::voice_commands::{self, Grammars};
 
 #[tauri::command]
 pub fn get_config(state: State<AppState>) -> AppConfig {
@@ -32,8 +51,13 @@ pub fn is_first_run(state: State<AppState>) -> bool {
 }
 
 #[tauri::command]
-pub fn get_system_info() -> SystemInfo {
-    system_info::get_system_info()
+pub fn get_system_info(state: State<AppState>) -> SystemInfo {
+    let inner = state.inner.lock().unwrap();
+    system_info::get_system_info(
+        &inner.app_data_dir,
+        &inner.config.language,
+        inner.config.force_cpu,
+    )
 }
 
 #[tauri::command]
@@ -76,8 +100,8 @@ pub fn load_model(state: State<AppState>, model_id: String) -> Result<(), String
     let path = models::get_model_path(&dir, &model_id)
         .ok_or_else(|| format!("Modèle introuvable : {}", model_id))?;
 
-    let ctx = transcription::load_model(&path).map_err(|e| e.to_string())?;
-    inner.whisper_ctx = Some(ctx);
+    let engine = transcription::load_model(&path, inner.config.force_cpu).map_err(|e| e.to_string())?;
+    inner.engine = Some(engine);
     inner.config.active_model = Some(model_id);
     inner.config.save(&dir).map_err(|e| e.to_string())?;
 
@@ -124,6 +148,48 @@ pub fn update_hotkey_ptt(
     Ok(())
 }
 
+#[tauri::command]
+pub fn update_hotkey_undo(
+    app: AppHandle,
+    state: State<AppState>,
+    new_hotkey: String,
+) -> Result<(), String> {
+    {
+        let mut inner = state.inner.lock().unwrap();
+        inner.config.hotkey_undo = new_hotkey;
+        let dir = inner.app_data_dir.clone();
+        inner.config.save(&dir).map_err(|e| e.to_string())?;
+    }
+
+    // Re-register all hotkeys
+    hotkey::unregister_all(&app).map_err(|e| e.to_string())?;
+    hotkey::register_all(&app).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_undo_depth(state: State<AppState>, depth: usize) -> Result<(), String> {
+    if depth == 0 {
+        return Err("La profondeur d'annulation doit être d'au moins 1".to_string());
+    }
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.undo_depth = depth;
+    let excess = inner.insertions.len().saturating_sub(depth);
+    inner.insertions.drain(..excess);
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+/// Erase the last dictation; returns the removed text.
+#[tauri::command]
+pub async fn undo_last_dictation(app: AppHandle) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || undo::undo_last(&app))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn suspend_hotkey(app: AppHandle) -> Result<(), String> {
     hotkey::unregister_all(&app).map_err(|e| e.to_string())
@@ -166,6 +232,288 @@ pub fn set_audio_device(state: State<AppState>, device_name: Option<String>) ->
     inner.config.save(&dir).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn set_force_cpu(state: State<AppState>, enabled: bool) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.force_cpu = enabled;
+    // Drop the loaded models so the next transcription reloads them on the new backend
+    inner.engine = None;
+    inner.profile_engine = None;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_watch_folder(state: State<AppState>, watch_folder: WatchFolderConfig) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.watch_folder = watch_folder;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_phantom_phrases(state: State<AppState>) -> Result<PhantomPhrases, String> {
+    let inner = state.inner.lock().unwrap();
+    hallucination::load_phrases(&inner.app_data_dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_phantom_phrases(state: State<AppState>, phrases: PhantomPhrases) -> Result<(), String> {
+    let inner = state.inner.lock().unwrap();
+    hallucination::save_phrases(&inner.app_data_dir, &phrases).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_voice_commands(state: State<AppState>, enabled: bool) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.voice_commands = enabled;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_normalization(
+    state: State<AppState>,
+    normalization: NormalizationConfig,
+) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.normalization = normalization;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_redaction(state: State<AppState>, redaction: RedactionConfig) -> Result<(), String> {
+    redaction::validate(&redaction).map_err(|e| e.to_string())?;
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.redaction = redaction;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_rewrite(state: State<AppState>, rewrite: RewriteConfig) -> Result<(), String> {
+    rewrite::validate(&rewrite).map_err(|e| e.to_string())?;
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.rewrite = rewrite;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+/// Send sample text through an endpoint configuration without saving it.
+#[tauri::command]
+pub async fn test_rewrite(rewrite: RewriteConfig, text: String) -> Result<String, String> {
+    rewrite::rewrite(&text, &rewrite)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_hook(state: State<AppState>, hook: HookConfig) -> Result<(), String> {
+    hook::validate(&hook).map_err(|e| e.to_string())?;
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.hook = hook;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+/// Pipe sample text through a hook configuration without saving it.
+#[tauri::command]
+pub async fn test_hook(
+    state: State<'_, AppState>,
+    hook: HookConfig,
+    text: String,
+) -> Result<String, String> {
+    let (language, model) = {
+        let inner = state.inner.lock().unwrap();
+        (
+            inner.config.language.clone(),
+            inner.config.active_model.clone().unwrap_or_default(),
+        )
+    };
+    tokio::task::spawn_blocking(move || {
+        let context = HookContext {
+            language: &language,
+            model: &model,
+            duration_ms: 0,
+        };
+        hook::run(&text, &hook, &context)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_paste_chord(state: State<AppState>, chord: String) -> Result<(), String> {
+    chord::parse(&chord).map_err(|e| e.to_string())?;
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.paste_chord = chord;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_clipboard_restore(
+    state: State<AppState>,
+    restore: ClipboardRestoreConfig,
+) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.clipboard_restore = restore;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_output_mode(
+    state: State<AppState>,
+    mode: OutputMode,
+    typing: TypingConfig,
+) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.output_mode = mode;
+    inner.config.typing = typing;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_input_backend(state: State<AppState>, backend: InputBackend) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.input_backend = backend;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_continuation(
+    state: State<AppState>,
+    continuation: ContinuationConfig,
+) -> Result<(), String> {
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.continuation = continuation;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+/// Session type and which injection backends can be used on this machine.
+#[tauri::command]
+pub fn get_injection_status() -> InjectionStatus {
+    injection::status()
+}
+
+#[tauri::command]
+pub fn set_sinks(state: State<AppState>, sinks: Vec<OutputSink>) -> Result<(), String> {
+    for sink in &sinks {
+        sinks::validate(sink).map_err(|e| e.to_string())?;
+    }
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.sinks = sinks;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+/// Send sample text to a sink configuration without saving it.
+#[tauri::command]
+pub async fn test_sink(
+    state: State<'_, AppState>,
+    sink: OutputSink,
+    text: String,
+) -> Result<(), String> {
+    let (language, model, window) = {
+        let inner = state.inner.lock().unwrap();
+        (
+            inner.config.language.clone(),
+            inner.config.active_model.clone().unwrap_or_default(),
+            inner.target_window.clone(),
+        )
+    };
+    let context = SinkContext {
+        language: &language,
+        model: &model,
+        window: window.as_ref(),
+        time: Local::now(),
+    };
+    sinks::send(&sink, &text, &context)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_profiles(state: State<AppState>, profiles: Vec<AppProfile>) -> Result<(), String> {
+    for profile in &profiles {
+        if let Some(chord) = &profile.paste_chord {
+            chord::parse(chord).map_err(|e| format!("[{}] {}", profile.name, e))?;
+        }
+    }
+    let mut inner = state.inner.lock().unwrap();
+    inner.config.profiles = profiles;
+    let dir = inner.app_data_dir.clone();
+    inner.config.save(&dir).map_err(|e| e.to_string())
+}
+
+/// Window focused at the start of the last dictation, to help writing
+/// profile rules.
+#[tauri::command]
+pub fn get_last_active_window(state: State<AppState>) -> Option<ActiveWindow> {
+    let inner = state.inner.lock().unwrap();
+    inner.target_window.clone()
+}
+
+#[tauri::command]
+pub fn get_voice_commands(state: State<AppState>) -> Result<Grammars, String> {
+    let inner = state.inner.lock().unwrap();
+    voice_commands::load_grammars(&inner.app_data_dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_voice_commands(state: State<AppState>, grammars: Grammars) -> Result<(), String> {
+    let inner = state.inner.lock().unwrap();
+    voice_commands::save_grammars(&inner.app_data_dir, &grammars).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_snippets(state: State<AppState>) -> Result<Vec<Snippet>, String> {
+    let inner = state.inner.lock().unwrap();
+    snippets::load_snippets(&inner.app_data_dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn add_snippet(state: State<AppState>, trigger: String, text: String) -> Result<Snippet, String> {
+    let inner = state.inner.lock().unwrap();
+    snippets::add_snippet(&inner.app_data_dir, trigger, text).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_snippet(state: State<AppState>, snippet: Snippet) -> Result<(), String> {
+    let inner = state.inner.lock().unwrap();
+    snippets::update_snippet(&inner.app_data_dir, snippet).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_snippet(state: State<AppState>, id: String) -> Result<(), String> {
+    let inner = state.inner.lock().unwrap();
+    snippets::delete_snippet(&inner.app_data_dir, &id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_text_rules(state: State<AppState>) -> Result<TextRuleSets, String> {
+    let inner = state.inner.lock().unwrap();
+    postprocess::load_rules(&inner.app_data_dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_text_rules(state: State<AppState>, rules: TextRuleSets) -> Result<(), String> {
+    let inner = state.inner.lock().unwrap();
+    postprocess::save_rules(&inner.app_data_dir, &rules).map_err(|e| e.to_string())
+}
+
+/// Run a rule list over sample text without saving it.
+#[tauri::command]
+pub fn test_text_rules(rules: Vec<TextRule>, text: String) -> Result<String, String> {
+    postprocess::run_rules(&text, &rules).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn mark_setup_complete(state: State<AppState>) -> Result<(), String> {
     let mut inner = state.inner.lock().unwrap();
@@ -198,6 +546,61 @@ pub async fn test_microphone(
     .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn transcribe_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    output_path: Option<String>,
+) -> Result<TranscriptionResult, String> {
+    let inner_arc = state.inner.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        file_transcription::transcribe_file(&app, &inner_arc, std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    // Optionally write the transcript next to the source, format picked from the extension
+    if let Some(output_path) = output_path {
+        let output_path = std::path::PathBuf::from(output_path);
+        let format = ExportFormat::from_path(&output_path).unwrap_or(ExportFormat::Txt);
+        export::write(&result, format, &output_path).map_err(|e| e.to_string())?;
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn benchmark_models(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<BenchmarkResult>, String> {
+    let (dir, force_cpu) = {
+        let inner = state.inner.lock().unwrap();
+        (inner.app_data_dir.clone(), inner.config.force_cpu)
+    };
+    tokio::task::spawn_blocking(move || benchmark::run(&app, &dir, force_cpu))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_benchmarks(state: State<AppState>) -> Vec<BenchmarkResult> {
+    let inner = state.inner.lock().unwrap();
+    benchmark::load_results(&inner.app_data_dir)
+}
+
+#[tauri::command]
+pub fn export_transcript(
+    result: TranscriptionResult,
+    format: ExportFormat,
+    path: String,
+) -> Result<(), String> {
+    export::write(&result, format, std::path::Path::new(&path)).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn check_permissions() -> PermissionStatus {
     permissions::check_permissions()
@@ -1,13 +1,20 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::errors::{AppError, AppResult};
+use crate::export::ExportFormat;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub hotkey: String,
     #[serde(default)]
     pub hotkey_ptt: String,
+    /// Erases the last dictation; empty to disable
+    #[serde(default)]
+    pub hotkey_undo: String,
+    /// Number of dictations that can be undone in a row
+    #[serde(default = "default_undo_depth")]
+    pub undo_depth: usize,
     pub auto_paste: bool,
     pub active_model: Option<String>,
     pub language: String,
@@ -15,12 +22,404 @@ pub struct AppConfig {
     #[serde(default = "default_ui_locale")]
     pub ui_locale: String,
     pub first_run_complete: bool,
+    /// Skip GPU initialisation and run Whisper on the CPU
+    #[serde(default)]
+    pub force_cpu: bool,
+    #[serde(default)]
+    pub watch_folder: WatchFolderConfig,
+    #[serde(default)]
+    pub filter: FilterConfig,
+    /// Replace spoken punctuation ("comma", "new line") with the characters;
+    /// the escape word keeps one literal ("literal comma"). Off by default: it
+    /// rewrites words the user may mean literally
+    #[serde(default)]
+    pub voice_commands: bool,
+    #[serde(default)]
+    pub normalization: NormalizationConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub rewrite: RewriteConfig,
+    #[serde(default)]
+    pub hook: HookConfig,
+    /// Keys pressed to paste, named by the characters they type in the active
+    /// layout, e.g. "Ctrl+Shift+V"
+    #[serde(default = "default_paste_chord")]
+    pub paste_chord: String,
+    #[serde(default)]
+    pub clipboard_restore: ClipboardRestoreConfig,
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    #[serde(default)]
+    pub input_backend: InputBackend,
+    #[serde(default)]
+    pub sinks: Vec<OutputSink>,
+    #[serde(default)]
+    pub typing: TypingConfig,
+    #[serde(default)]
+    pub continuation: ContinuationConfig,
+    /// Overrides for the application focused when a dictation starts
+    #[serde(default)]
+    pub profiles: Vec<AppProfile>,
+}
+
+/// Batch transcription of audio files dropped into a directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchFolderConfig {
+    pub enabled: bool,
+    pub path: Option<String>,
+    /// Format of the sidecar transcript written next to each file
+    pub output_format: ExportFormat,
+    /// Move processed files to a `done` subfolder instead of leaving them in place
+    pub move_to_done: bool,
+}
+
+/// Hallucination filter applied to every transcription.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterConfig {
+    pub enabled: bool,
+    /// RMS level of the audio under a segment below which the segment is
+    /// dropped as decoded from silence (speech is typically 0.01-0.1; 0
+    /// disables the check). whisper-rs exposes no per-segment no-speech
+    /// probability, so the audio level stands in for it.
+    #[serde(default = "default_silence_rms_threshold")]
+    pub silence_rms_threshold: f32,
+    pub drop_phantom_phrases: bool,
+    pub collapse_repetitions: bool,
+}
+
+/// Spoken numbers and amounts rewritten in written form, per category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizationConfig {
+    pub enabled: bool,
+    /// "twenty three" -> "23"
+    pub numbers: bool,
+    /// "twenty third" -> "23rd"
+    pub ordinals: bool,
+    pub dates: bool,
+    pub times: bool,
+    pub currencies: bool,
+    pub percentages: bool,
+}
+
+impl Default for NormalizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            numbers: true,
+            ordinals: true,
+            dates: true,
+            times: true,
+            currencies: true,
+            percentages: true,
+        }
+    }
+}
+
+/// Personal data detected in a dictation before it reaches the clipboard, and
+/// in file and watch-folder transcripts before they are written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    pub emails: RedactionAction,
+    pub phones: RedactionAction,
+    pub ibans: RedactionAction,
+    pub credit_cards: RedactionAction,
+    #[serde(default)]
+    pub custom: Vec<RedactionPattern>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionAction {
+    /// Not detected
+    Off,
+    /// Letters and digits replaced with `*`
+    Mask,
+    /// Whole match replaced with a label such as `[EMAIL]`
+    Placeholder,
+    /// Text left as-is, a `redaction-warning` event is emitted
+    Warn,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionPattern {
+    /// Reported in warnings and used for the placeholder (`[NAME]`)
+    pub name: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    pub action: RedactionAction,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            emails: RedactionAction::Placeholder,
+            phones: RedactionAction::Placeholder,
+            ibans: RedactionAction::Mask,
+            credit_cards: RedactionAction::Mask,
+            custom: Vec::new(),
+        }
+    }
+}
+
+/// Rewrite of each dictation by a local OpenAI-compatible LLM server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewriteConfig {
+    pub enabled: bool,
+    /// Chat-completions URL, e.g. http://127.0.0.1:11434/v1/chat/completions
+    pub endpoint: String,
+    pub model: String,
+    pub system_prompt: String,
+    pub timeout_secs: u64,
+    /// Accept endpoints outside this machine
+    pub allow_remote: bool,
+}
+
+impl Default for RewriteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://127.0.0.1:11434/v1/chat/completions".to_string(),
+            model: "llama3.2".to_string(),
+            system_prompt: "Correct the grammar and punctuation of the user's text. \
+                Keep its language and meaning. Reply with the corrected text only."
+                .to_string(),
+            timeout_secs: 20,
+            allow_remote: false,
+        }
+    }
+}
+
+/// External executable each dictation is piped through (stdin -> stdout).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookConfig {
+    pub enabled: bool,
+    /// Program path or name looked up in PATH
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub timeout_secs: u64,
+}
+
+impl Default for HookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: String::new(),
+            args: Vec::new(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Put back what the user had copied once a dictation has been pasted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardRestoreConfig {
+    pub enabled: bool,
+    /// Time left to the target application to read the pasted text
+    pub delay_ms: u64,
+}
+
+impl Default for ClipboardRestoreConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay_ms: 1000,
+        }
+    }
+}
+
+/// How a dictation is inserted into the focused application.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// Copy to the clipboard, then paste if `auto_paste` is on
+    #[default]
+    Paste,
+    /// Type the text as keystrokes, leaving the clipboard untouched
+    Type,
+    /// Only the output sinks receive the dictation
+    SinksOnly,
+}
+
+/// How keystrokes reach the focused window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputBackend {
+    /// Chosen from the session type, falling back to the next one on failure
+    #[default]
+    Auto,
+    /// enigo: XTest on X11, system APIs on macOS and Windows
+    Native,
+    Wtype,
+    Ydotool,
+    /// Virtual keyboard on /dev/uinput (shortcuts only)
+    Uinput,
+}
+
+impl InputBackend {
+    pub fn name(self) -> &'static str {
+        match self {
+            InputBackend::Auto => "auto",
+            InputBackend::Native => "enigo",
+            InputBackend::Wtype => "wtype",
+            InputBackend::Ydotool => "ydotool",
+            InputBackend::Uinput => "uinput",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypingConfig {
+    /// Pause after each character, for applications that drop fast input
+    pub char_delay_ms: u64,
+    /// Characters typed per main-thread dispatch
+    pub chunk_size: usize,
+}
+
+impl Default for TypingConfig {
+    fn default() -> Self {
+        Self {
+            char_delay_ms: 0,
+            chunk_size: 64,
+        }
+    }
+}
+
+/// Spacing and capitalization of a dictation that follows the previous one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuationConfig {
+    pub enabled: bool,
+    /// Longest pause after the previous insertion for the two to be joined
+    pub window_secs: u64,
+    /// Lowercase the first word of a dictation continuing a sentence. Off by
+    /// default: it also lowercases names ("Paris")
+    #[serde(default)]
+    pub lowercase_first_word: bool,
+}
+
+impl Default for ContinuationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 30,
+            lowercase_first_word: false,
+        }
+    }
+}
+
+/// Where an output sink sends the dictation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SinkTarget {
+    /// Appended to; the path takes the template placeholders, e.g. {date}
+    /// for a daily notes file
+    File { path: String },
+    /// FIFO created beforehand with mkfifo, or a Windows named pipe
+    NamedPipe { path: String },
+    /// JSON POST, limited to this machine unless `allow_remote` is set
+    Webhook {
+        url: String,
+        #[serde(default)]
+        allow_remote: bool,
+    },
+    /// X11 primary selection (middle-click paste)
+    PrimarySelection,
+}
+
+/// Destination a dictation is sent to besides the paste / type output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputSink {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub target: SinkTarget,
+    /// Template with {text}, {timestamp}, {date}, {time}, {language}, {model}
+    /// and {window}. Unset: "[{timestamp}] {text}" for files, "{text}" otherwise
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Settings applied when the focused application matches `window_classes`.
+/// Unset fields keep the global value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppProfile {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// X11 WM_CLASS class or instance names, case-insensitive
+    pub window_classes: Vec<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub voice_commands: Option<bool>,
+    /// Rule list from text_rules.json run instead of the shared and language lists
+    #[serde(default)]
+    pub text_rules: Option<String>,
+    #[serde(default)]
+    pub auto_paste: Option<bool>,
+    #[serde(default)]
+    pub paste_chord: Option<String>,
+    #[serde(default)]
+    pub output_mode: Option<OutputMode>,
+    /// Names of the sinks used for this application, replacing the ones
+    /// enabled globally
+    #[serde(default)]
+    pub sinks: Option<Vec<String>>,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            silence_rms_threshold: default_silence_rms_threshold(),
+            drop_phantom_phrases: true,
+            collapse_repetitions: true,
+        }
+    }
+}
+
+impl Default for WatchFolderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            output_format: ExportFormat::Txt,
+            move_to_done: true,
+        }
+    }
 }
 
 fn default_ui_locale() -> String {
     "en".to_string()
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_silence_rms_threshold() -> f32 {
+    0.004
+}
+
+fn default_undo_depth() -> usize {
+    5
+}
+
+fn default_paste_chord() -> String {
+    if cfg!(target_os = "macos") {
+        "Cmd+V".to_string()
+    } else {
+        "Ctrl+V".to_string()
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -30,22 +429,40 @@ impl Default for AppConfig {
                 "Ctrl+Shift+Space".to_string()
             },
             hotkey_ptt: "Insert".to_string(),
+            hotkey_undo: String::new(),
+            undo_depth: default_undo_depth(),
             auto_paste: true,
             active_model: None,
             language: "fr".to_string(),
             audio_device: None,
             ui_locale: "en".to_string(),
             first_run_complete: false,
+            force_cpu: false,
+            watch_folder: WatchFolderConfig::default(),
+            filter: FilterConfig::default(),
+            voice_commands: false,
+            normalization: NormalizationConfig::default(),
+            redaction: RedactionConfig::default(),
+            rewrite: RewriteConfig::default(),
+            hook: HookConfig::default(),
+            paste_chord: default_paste_chord(),
+            clipboard_restore: ClipboardRestoreConfig::default(),
+            output_mode: OutputMode::default(),
+            input_backend: InputBackend::default(),
+            sinks: Vec::new(),
+            typing: TypingConfig::default(),
+            continuation: ContinuationConfig::default(),
+            profiles: Vec::new(),
         }
     }
 }
 
 impl AppConfig {
-    fn config_path(app_data_dir: &PathBuf) -> PathBuf {
+    fn config_path(app_data_dir: &Path) -> PathBuf {
         app_data_dir.join("config.json")
     }
 
-    pub fn load(app_data_dir: &PathBuf) -> AppResult<Self> {
+    pub fn load(app_data_dir: &Path) -> AppResult<Self> {
         let path = Self::config_path(app_data_dir);
 
         if !path.exists() {
@@ -61,7 +478,7 @@ impl AppConfig {
             .map_err(|e| AppError::Config(format!("Parsing impossible : {}", e)))
     }
 
-    pub fn save(&self, app_data_dir: &PathBuf) -> AppResult<()> {
+    pub fn save(&self, app_data_dir: &Path) -> AppResult<()> {
         std::fs::create_dir_all(app_data_dir)?;
         let path = Self::config_path(app_data_dir);
         let json = serde_json::to_string_pretty(self)
@@ -0,0 +1,176 @@
+//! Joining consecutive dictations: a dictation inserted shortly after the
+//! previous one, in the same window, gets a leading space and a capital
+//! letter when the previous text ended a sentence.
+
+use std::time::{Duration, Instant};
+
+use crate::active_window::ActiveWindow;
+use crate::config::ContinuationConfig;
+
+/// Text the app last inserted, and where and when.
+#[derive(Debug, Clone)]
+pub struct Insertion {
+    pub text: String,
+    /// None where the focused window cannot be detected; only the time limit
+    /// applies then
+    pub window: Option<u64>,
+    pub at: Instant,
+}
+
+impl Insertion {
+    pub fn new(text: &str, window: Option<&ActiveWindow>) -> Self {
+        Self {
+            text: text.to_string(),
+            window: window.map(|w| w.id),
+            at: Instant::now(),
+        }
+    }
+}
+
+/// Whether `text` follows `previous` directly: same window, and within the
+/// configured delay.
+fn continues(previous: &Insertion, window: Option<&ActiveWindow>, config: &ContinuationConfig) -> bool {
+    previous.window == window.map(|w| w.id)
+        && previous.at.elapsed() <= Duration::from_secs(config.window_secs)
+}
+
+/// Punctuation a dictation can start with that sticks to the previous word.
+/// French puts a space before the high signs (; : ! ?).
+fn attaches_left(c: char, language: &str) -> bool {
+    matches!(c, ',' | '.' | ')' | ']' | '}' | '…' | '»' | '%')
+        || (!language.starts_with("fr") && matches!(c, ';' | ':' | '!' | '?'))
+}
+
+fn ends_sentence(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
+}
+
+/// "I", "I'm", "NASA", "iPhone": words whose case must survive mid-sentence.
+/// Names ("Paris") cannot be told apart, hence the opt-in.
+fn keeps_case(word: &str) -> bool {
+    let letters: Vec<char> = word.chars().take_while(|c| c.is_alphanumeric()).collect();
+    letters == ['I'] || letters.iter().skip(1).any(|c| c.is_uppercase())
+}
+
+fn with_first_letter(text: &str, upper: bool) -> String {
+    let Some(first) = text.chars().next() else {
+        return String::new();
+    };
+    let rest = &text[first.len_utf8()..];
+    if upper {
+        first.to_uppercase().chain(rest.chars()).collect()
+    } else {
+        first.to_lowercase().chain(rest.chars()).collect()
+    }
+}
+
+/// `text` adjusted to follow `previous`, or unchanged when it does not
+/// continue it.
+pub fn join(
+    text: &str,
+    language: &str,
+    previous: Option<&Insertion>,
+    window: Option<&ActiveWindow>,
+    config: &ContinuationConfig,
+) -> String {
+    let Some(previous) = previous.filter(|p| config.enabled && continues(p, window, config)) else {
+        return text.to_string();
+    };
+    let text = text.trim_start();
+    let (Some(first), Some(last)) = (text.chars().next(), previous.text.chars().last()) else {
+        return text.to_string();
+    };
+
+    let mut joined = String::new();
+    let opens = matches!(last, '(' | '[' | '{' | '«' | '/' | '-');
+    if !last.is_whitespace() && !opens && !attaches_left(first, language) {
+        joined.push(' ');
+    }
+
+    match previous.text.trim_end().chars().last() {
+        // Start of a new sentence, or of a new paragraph
+        Some(c) if ends_sentence(c) => joined.push_str(&with_first_letter(text, true)),
+        None => joined.push_str(&with_first_letter(text, true)),
+        // Whisper capitalizes every dictation as if it started a sentence
+        Some(c)
+            if config.lowercase_first_word && (c.is_alphanumeric() || matches!(c, ',' | ';' | ':')) =>
+        {
+            let word = text.split_whitespace().next().unwrap_or("");
+            joined.push_str(&with_first_letter(text, keeps_case(word)));
+        }
+        Some(_) => joined.push_str(text),
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: u64) -> ActiveWindow {
+        ActiveWindow {
+            id,
+            class: "Editor".to_string(),
+            instance: "editor".to_string(),
+        }
+    }
+
+    fn after(previous: &str, text: &str, language: &str) -> String {
+        let previous = Insertion::new(previous, Some(&window(1)));
+        join(text, language, Some(&previous), Some(&window(1)), &ContinuationConfig::default())
+    }
+
+    #[test]
+    fn new_sentence_is_capitalized_and_spaced() {
+        assert_eq!(after("It works.", "then it stops", "en"), " Then it stops");
+        assert_eq!(after("Vraiment ?", "oui", "fr"), " Oui");
+    }
+
+    #[test]
+    fn names_keep_their_capital_mid_sentence() {
+        assert_eq!(after("We flew to", "Paris today", "en"), " Paris today");
+        assert_eq!(after("Bonjour,", "Marie", "fr"), " Marie");
+    }
+
+    #[test]
+    fn first_word_is_lowercased_when_opted_in() {
+        let config = ContinuationConfig {
+            lowercase_first_word: true,
+            ..ContinuationConfig::default()
+        };
+        let previous = Insertion::new("I think", Some(&window(1)));
+        let joined = |text| join(text, "en", Some(&previous), Some(&window(1)), &config);
+        assert_eq!(joined("The plan works"), " the plan works");
+        assert_eq!(joined("I agree"), " I agree");
+        assert_eq!(joined("NASA agrees"), " NASA agrees");
+    }
+
+    #[test]
+    fn no_double_space() {
+        assert_eq!(after("Done. ", "Next", "en"), "Next");
+        assert_eq!(after("Done.", "  Next", "en"), " Next");
+    }
+
+    #[test]
+    fn punctuation_attaches_by_language() {
+        assert_eq!(after("Really", "?", "en"), "?");
+        assert_eq!(after("Vraiment", "?", "fr"), " ?");
+        assert_eq!(after("Voici", ": la liste", "fr"), " : la liste");
+        assert_eq!(after("Here", ", then", "en"), ", then");
+    }
+
+    #[test]
+    fn openers_take_no_space() {
+        assert_eq!(after("See (", "page 3", "en"), "page 3");
+        assert_eq!(after("Il a dit «", "bonjour", "fr"), "bonjour");
+    }
+
+    #[test]
+    fn other_window_leaves_text_unchanged() {
+        let previous = Insertion::new("It works.", Some(&window(1)));
+        let config = ContinuationConfig::default();
+        let joined = join("then it stops", "en", Some(&previous), Some(&window(2)), &config);
+        assert_eq!(joined, "then it stops");
+        assert_eq!(join("then", "en", None, Some(&window(1)), &config), "then");
+    }
+}
@@ -0,0 +1,191 @@
+//! Transcription backends. The rest of the app only talks to
+//! `TranscriptionEngine`, so the Whisper model can be swapped for the
+//! deterministic mock (feature `mock-engine`) when exercising the pipeline.
+
+use whisper_rs::WhisperContext;
+
+use crate::errors::AppResult;
+use crate::transcription::{self, TranscribeOptions, TranscriptionResult};
+
+pub type ProgressCallback = Box<dyn FnMut(i32)>;
+
+pub trait TranscriptionEngine: Send + Sync {
+    /// Transcribe 16 kHz mono samples. `on_progress` receives a percentage.
+    fn transcribe(
+        &self,
+        audio: &[f32],
+        options: &TranscribeOptions,
+        on_progress: Option<ProgressCallback>,
+    ) -> AppResult<TranscriptionResult>;
+
+    /// Short backend name, for logs.
+    fn name(&self) -> &'static str;
+}
+
+/// whisper.cpp backend through whisper-rs.
+pub struct WhisperEngine {
+    ctx: WhisperContext,
+}
+
+impl WhisperEngine {
+    pub fn new(ctx: WhisperContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TranscriptionEngine for WhisperEngine {
+    fn transcribe(
+        &self,
+        audio: &[f32],
+        options: &TranscribeOptions,
+        on_progress: Option<ProgressCallback>,
+    ) -> AppResult<TranscriptionResult> {
+        transcription::transcribe_segments(&self.ctx, audio, options, on_progress)
+    }
+
+    fn name(&self) -> &'static str {
+        "whisper"
+    }
+}
+
+#[cfg(any(test, feature = "mock-engine"))]
+pub use mock::MockEngine;
+
+#[cfg(any(test, feature = "mock-engine"))]
+mod mock {
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{ProgressCallback, TranscriptionEngine};
+    use crate::errors::{AppError, AppResult};
+    use crate::transcription::{Segment, TranscribeOptions, TranscriptionResult};
+
+    /// Returns scripted transcripts in order, cycling back to the first one
+    /// when the script is exhausted. No model, no randomness.
+    pub struct MockEngine {
+        script: Vec<String>,
+        next: AtomicUsize,
+    }
+
+    impl MockEngine {
+        pub fn new(script: Vec<String>) -> Self {
+            Self {
+                script,
+                next: AtomicUsize::new(0),
+            }
+        }
+
+        /// One transcript per non-empty line of `path`.
+        pub fn from_file(path: &Path) -> AppResult<Self> {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| AppError::Transcription(format!("Script du moteur factice illisible : {}", e)))?;
+            let script: Vec<String> = content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect();
+            Ok(Self::new(script))
+        }
+    }
+
+    impl TranscriptionEngine for MockEngine {
+        fn transcribe(
+            &self,
+            audio: &[f32],
+            options: &TranscribeOptions,
+            on_progress: Option<ProgressCallback>,
+        ) -> AppResult<TranscriptionResult> {
+            let duration_ms = audio.len() as i64 * 1000 / 16000;
+            let text = if self.script.is_empty() {
+                String::new()
+            } else {
+                let i = self.next.fetch_add(1, Ordering::SeqCst);
+                self.script[i % self.script.len()].clone()
+            };
+
+            if let Some(mut progress) = on_progress {
+                progress(100);
+            }
+
+            let segments = if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![Segment {
+                    start_ms: 0,
+                    end_ms: duration_ms,
+                    text: text.clone(),
+                    confidence: 1.0,
+                    speaker: options.diarize.then_some(1),
+                }]
+            };
+
+            Ok(TranscriptionResult {
+                text,
+                segments,
+                language: options.language.clone(),
+                duration_ms,
+            })
+        }
+
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(diarize: bool) -> TranscribeOptions {
+        TranscribeOptions {
+            language: "en".to_string(),
+            timestamps: true,
+            diarize,
+        }
+    }
+
+    #[test]
+    fn mock_cycles_through_its_script() {
+        let engine = MockEngine::new(vec!["first".to_string(), "second".to_string()]);
+        let texts: Vec<String> = (0..3)
+            .map(|_| engine.transcribe(&[0.0; 16000], &options(false), None).unwrap().text)
+            .collect();
+        assert_eq!(texts, ["first", "second", "first"]);
+    }
+
+    #[test]
+    fn mock_script_is_one_transcript_per_line() {
+        let path = std::env::temp_dir().join(format!("localwhisper-script-{}", std::process::id()));
+        std::fs::write(&path, "  first line \n\nsecond line\n").unwrap();
+        let engine = MockEngine::from_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let texts: Vec<String> = (0..2)
+            .map(|_| engine.transcribe(&[], &options(false), None).unwrap().text)
+            .collect();
+        assert_eq!(texts, ["first line", "second line"]);
+    }
+
+    #[test]
+    fn mock_result_spans_the_audio() {
+        let engine = MockEngine::new(vec!["hello there".to_string()]);
+        let result = engine.transcribe(&[0.0; 32000], &options(true), None).unwrap();
+        assert_eq!(result.duration_ms, 2000);
+        assert_eq!(result.segments.len(), 1);
+        assert_eq!(result.segments[0].end_ms, 2000);
+        assert_eq!(result.segments[0].speaker, Some(1));
+    }
+
+    #[test]
+    fn mock_reports_completion() {
+        let engine = MockEngine::new(Vec::new());
+        let done = std::rc::Rc::new(std::cell::Cell::new(0));
+        let seen = done.clone();
+        let result = engine
+            .transcribe(&[], &options(false), Some(Box::new(move |p| seen.set(p))))
+            .unwrap();
+        assert_eq!(done.get(), 100);
+        assert!(result.text.is_empty() && result.segments.is_empty());
+    }
+}
@@ -20,6 +20,18 @@ pub enum AppError {
     #[error("Erreur de téléchargement : {0}")]
     Download(String),
 
+    #[error("Erreur de réécriture : {0}")]
+    Rewrite(String),
+
+    #[error("Erreur du script externe : {0}")]
+    Hook(String),
+
+    #[error("Erreur de sortie : {0}")]
+    Sink(String),
+
+    #[error("Annulation impossible : {0}")]
+    Undo(String),
+
     #[error("Erreur I/O : {0}")]
     Io(#[from] std::io::Error),
 }
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::errors::{AppError, AppResult};
+use crate::transcription::TranscriptionResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Srt,
+    Vtt,
+    Txt,
+    Json,
+    Md,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Srt => "srt",
+            ExportFormat::Vtt => "vtt",
+            ExportFormat::Txt => "txt",
+            ExportFormat::Json => "json",
+            ExportFormat::Md => "md",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "srt" => Some(ExportFormat::Srt),
+            "vtt" => Some(ExportFormat::Vtt),
+            "txt" => Some(ExportFormat::Txt),
+            "json" => Some(ExportFormat::Json),
+            "md" | "markdown" => Some(ExportFormat::Md),
+            _ => None,
+        }
+    }
+}
+
+/// Format milliseconds as `HH:MM:SS<sep>mmm` (SRT uses ',', WebVTT uses '.').
+fn format_timestamp(ms: i64, separator: char) -> String {
+    let ms = ms.max(0);
+    let hours = ms / 3_600_000;
+    let minutes = (ms % 3_600_000) / 60_000;
+    let seconds = (ms % 60_000) / 1000;
+    let millis = ms % 1000;
+    format!("{:02}:{:02}:{:02}{}{:03}", hours, minutes, seconds, separator, millis)
+}
+
+fn speaker_label(speaker: u32) -> String {
+    format!("Speaker {}", speaker)
+}
+
+/// Cue text, prefixed with "Speaker N: " whenever the speaker changes.
+fn cue_texts(result: &TranscriptionResult) -> Vec<String> {
+    let mut previous = None;
+    result
+        .segments
+        .iter()
+        .map(|seg| {
+            let text = match seg.speaker {
+                Some(speaker) if previous != Some(speaker) => {
+                    format!("{}: {}", speaker_label(speaker), seg.text)
+                }
+                _ => seg.text.clone(),
+            };
+            previous = seg.speaker;
+            text
+        })
+        .collect()
+}
+
+fn to_srt(result: &TranscriptionResult) -> String {
+    let mut out = String::new();
+    for (i, (seg, text)) in result.segments.iter().zip(cue_texts(result)).enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(seg.start_ms, ','),
+            format_timestamp(seg.end_ms, ','),
+            text
+        ));
+    }
+    out
+}
+
+fn to_vtt(result: &TranscriptionResult) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for (seg, text) in result.segments.iter().zip(cue_texts(result)) {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(seg.start_ms, '.'),
+            format_timestamp(seg.end_ms, '.'),
+            text
+        ));
+    }
+    out
+}
+
+/// Plain text; with speaker labels, one paragraph per speaker turn.
+fn to_txt(result: &TranscriptionResult) -> String {
+    if result.segments.iter().all(|s| s.speaker.is_none()) {
+        return format!("{}\n", result.text);
+    }
+
+    let mut turns: Vec<(Option<u32>, Vec<&str>)> = Vec::new();
+    for seg in &result.segments {
+        match turns.last_mut() {
+            Some((speaker, texts)) if *speaker == seg.speaker => texts.push(&seg.text),
+            _ => turns.push((seg.speaker, vec![&seg.text])),
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|(speaker, texts)| match speaker {
+            Some(s) => format!("{}: {}\n", speaker_label(s), texts.join(" ")),
+            None => format!("{}\n", texts.join(" ")),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn to_json(result: &TranscriptionResult) -> AppResult<String> {
+    serde_json::to_string_pretty(result)
+        .map_err(|e| AppError::Config(format!("Sérialisation impossible : {}", e)))
+}
+
+fn to_markdown(result: &TranscriptionResult) -> String {
+    let mut out = String::from("# Transcription\n\n");
+    for seg in &result.segments {
+        // Drop the milliseconds: headers only need to be skimmable
+        let stamp = format_timestamp(seg.start_ms, '.');
+        let header = match seg.speaker {
+            Some(speaker) => format!("{} — {}", &stamp[..8], speaker_label(speaker)),
+            None => stamp[..8].to_string(),
+        };
+        out.push_str(&format!("## {}\n\n{}\n\n", header, seg.text));
+    }
+    out
+}
+
+pub fn render(result: &TranscriptionResult, format: ExportFormat) -> AppResult<String> {
+    Ok(match format {
+        ExportFormat::Srt => to_srt(result),
+        ExportFormat::Vtt => to_vtt(result),
+        ExportFormat::Txt => to_txt(result),
+        ExportFormat::Json => to_json(result)?,
+        ExportFormat::Md => to_markdown(result),
+    })
+}
+
+pub fn write(result: &TranscriptionResult, format: ExportFormat, path: &Path) -> AppResult<()> {
+    let content = render(result, format)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    log::info!("Transcription exportée : {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::Segment;
+
+    fn segment(start_ms: i64, end_ms: i64, text: &str, speaker: Option<u32>) -> Segment {
+        Segment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            confidence: 1.0,
+            speaker,
+        }
+    }
+
+    fn result(segments: Vec<Segment>) -> TranscriptionResult {
+        TranscriptionResult {
+            text: segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" "),
+            duration_ms: segments.last().map_or(0, |s| s.end_ms),
+            segments,
+            language: "en".to_string(),
+        }
+    }
+
+    #[test]
+    fn timestamps_use_the_format_separator() {
+        assert_eq!(format_timestamp(0, ','), "00:00:00,000");
+        assert_eq!(format_timestamp(61_005, ','), "00:01:01,005");
+        assert_eq!(format_timestamp(3_723_456, '.'), "01:02:03.456");
+        assert_eq!(format_timestamp(36_000_000, '.'), "10:00:00.000");
+        assert_eq!(format_timestamp(-20, ','), "00:00:00,000");
+    }
+
+    #[test]
+    fn srt_numbers_cues_from_one() {
+        let result = result(vec![
+            segment(0, 1_500, "Hello.", None),
+            segment(3_599_000, 3_601_250, "Still here.", None),
+        ]);
+        assert_eq!(
+            render(&result, ExportFormat::Srt).unwrap(),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello.\n\n\
+             2\n00:59:59,000 --> 01:00:01,250\nStill here.\n\n"
+        );
+    }
+
+    #[test]
+    fn vtt_has_a_header_and_no_cue_numbers() {
+        let result = result(vec![segment(3_600_000, 3_602_000, "Late cue.", None)]);
+        assert_eq!(
+            render(&result, ExportFormat::Vtt).unwrap(),
+            "WEBVTT\n\n01:00:00.000 --> 01:00:02.000\nLate cue.\n\n"
+        );
+    }
+
+    #[test]
+    fn speaker_is_labelled_only_when_it_changes() {
+        let result = result(vec![
+            segment(0, 1_000, "Hi.", Some(1)),
+            segment(1_000, 2_000, "How are you?", Some(1)),
+            segment(2_000, 3_000, "Fine.", Some(2)),
+            segment(3_000, 4_000, "Good.", Some(1)),
+        ]);
+        assert_eq!(
+            cue_texts(&result),
+            ["Speaker 1: Hi.", "How are you?", "Speaker 2: Fine.", "Speaker 1: Good."]
+        );
+        assert_eq!(
+            render(&result, ExportFormat::Txt).unwrap(),
+            "Speaker 1: Hi. How are you?\n\nSpeaker 2: Fine.\n\nSpeaker 1: Good.\n"
+        );
+    }
+
+    #[test]
+    fn markdown_headers_drop_the_milliseconds() {
+        let result = result(vec![
+            segment(83_456, 85_000, "First.", None),
+            segment(3_725_999, 3_727_000, "Second.", Some(2)),
+        ]);
+        assert_eq!(
+            render(&result, ExportFormat::Md).unwrap(),
+            "# Transcription\n\n## 00:01:23\n\nFirst.\n\n## 01:02:05 — Speaker 2\n\nSecond.\n\n"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use crate::audio_file;
+use crate::config::{FilterConfig, RedactionConfig};
+use crate::errors::{AppError, AppResult};
+use crate::hallucination;
+use crate::models;
+use crate::redaction::{self, RedactionWarning};
+use crate::state::InnerState;
+use crate::transcription::{self, Segment, TranscribeOptions, TranscriptionResult};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTranscriptionProgress {
+    pub path: String,
+    /// "decoding", "transcribing" or "done"
+    pub stage: String,
+    pub percent: f64,
+}
+
+fn emit_progress(app: &AppHandle, path: &str, stage: &str, percent: f64) {
+    let _ = app.emit(
+        "file-transcription-progress",
+        FileTranscriptionProgress {
+            path: path.to_string(),
+            stage: stage.to_string(),
+            percent,
+        },
+    );
+}
+
+/// Mask personal data in the full text and in each segment, so timed exports
+/// are covered too. Warnings are reported once, from the full text.
+fn redact(app: &AppHandle, result: TranscriptionResult, config: &RedactionConfig) -> TranscriptionResult {
+    if !config.enabled {
+        return result;
+    }
+    let warn = |warning: RedactionWarning| {
+        log::warn!("Donnée personnelle détectée : {}", warning.kind);
+        let _ = app.emit("redaction-warning", warning);
+    };
+    let segments = result
+        .segments
+        .into_iter()
+        .map(|segment| Segment {
+            text: redaction::redact(&segment.text, config, &|_| {}),
+            ..segment
+        })
+        .collect();
+    TranscriptionResult {
+        text: redaction::redact(&result.text, config, &warn),
+        segments,
+        ..result
+    }
+}
+
+/// Decode an audio file from disk and transcribe it with the loaded model.
+/// Blocking: call it from a worker thread.
+pub fn transcribe_file(
+    app: &AppHandle,
+    inner_arc: &Arc<Mutex<InnerState>>,
+    path: &Path,
+) -> AppResult<TranscriptionResult> {
+    if !path.is_file() {
+        return Err(AppError::Audio(format!("Fichier introuvable : {}", path.display())));
+    }
+    if !audio_file::is_supported(path) {
+        return Err(AppError::Audio(format!(
+            "Format non supporté : {} (attendu : {})",
+            path.display(),
+            audio_file::SUPPORTED_EXTENSIONS.join(", ")
+        )));
+    }
+
+    let path_str = path.display().to_string();
+    let (options, filter_config, redaction_config, app_data_dir): (
+        TranscribeOptions,
+        FilterConfig,
+        RedactionConfig,
+        PathBuf,
+    ) = {
+        let inner = inner_arc.lock().unwrap();
+        let diarize = inner
+            .config
+            .active_model
+            .as_deref()
+            .map(models::supports_tdrz)
+            .unwrap_or(false);
+        (
+            TranscribeOptions {
+                language: inner.config.language.clone(),
+                timestamps: true,
+                diarize,
+            },
+            inner.config.filter.clone(),
+            inner.config.redaction.clone(),
+            inner.app_data_dir.clone(),
+        )
+    };
+
+    emit_progress(app, &path_str, "decoding", 0.0);
+    let audio = audio_file::decode_file(path)?;
+
+    let engine = transcription::ensure_model(inner_arc, &app_data_dir)?;
+
+    emit_progress(app, &path_str, "transcribing", 0.0);
+    let handle = app.clone();
+    let progress_path = path_str.clone();
+    let result = engine.transcribe(
+        &audio,
+        &options,
+        Some(Box::new(move |percent: i32| {
+            emit_progress(&handle, &progress_path, "transcribing", percent as f64);
+        })),
+    )?;
+    let result = hallucination::apply(result, &audio, &filter_config, &app_data_dir);
+    let result = redact(app, result, &redaction_config);
+
+    emit_progress(app, &path_str, "done", 100.0);
+    log::info!(
+        "Fichier transcrit : {} ({} segments)",
+        path.display(),
+        result.segments.len()
+    );
+
+    Ok(result)
+}
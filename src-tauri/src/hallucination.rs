@@ -0,0 +1,392 @@
+//! Filtering of Whisper hallucinations: segments produced from silence, stock
+//! phrases learned from subtitled videos, and repetition loops.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::FilterConfig;
+use crate::errors::{AppError, AppResult};
+use crate::transcription::{Segment, TranscriptionResult};
+
+const PHRASES_FILE: &str = "phantom_phrases.json";
+/// Phrases listed under this key apply to every language.
+const ANY_LANGUAGE: &str = "*";
+/// Longest n-gram checked for repetition loops.
+const MAX_NGRAM: usize = 8;
+
+/// Per-language lists of phrases Whisper emits on near-silent input.
+pub type PhantomPhrases = HashMap<String, Vec<String>>;
+
+fn default_phrases() -> PhantomPhrases {
+    let entries: &[(&str, &[&str])] = &[
+        (
+            "en",
+            &[
+                "Thank you for watching",
+                "Thanks for watching",
+                "Please subscribe to my channel",
+                "Like and subscribe",
+                "See you in the next video",
+            ],
+        ),
+        (
+            "fr",
+            &[
+                "Sous-titres réalisés par la communauté d'Amara.org",
+                "Sous-titres réalisés para la communauté d'Amara.org",
+                "Sous-titrage Société Radio-Canada",
+                "Sous-titrage ST' 501",
+                "Merci d'avoir regardé cette vidéo",
+                "Abonnez-vous",
+            ],
+        ),
+        (
+            "es",
+            &[
+                "Subtítulos realizados por la comunidad de Amara.org",
+                "Gracias por ver el video",
+            ],
+        ),
+        (
+            "de",
+            &[
+                "Untertitel im Auftrag des ZDF",
+                "Untertitel der Amara.org-Community",
+                "Vielen Dank fürs Zuschauen",
+            ],
+        ),
+    ];
+
+    entries
+        .iter()
+        .map(|(lang, phrases)| {
+            (
+                lang.to_string(),
+                phrases.iter().map(|p| p.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+/// Load the editable phrase list from `app_data_dir`, creating it with the
+/// built-in defaults on first use.
+pub fn load_phrases(app_data_dir: &Path) -> AppResult<PhantomPhrases> {
+    let path = app_data_dir.join(PHRASES_FILE);
+
+    if !path.exists() {
+        let phrases = default_phrases();
+        save_phrases(app_data_dir, &phrases)?;
+        return Ok(phrases);
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| AppError::Config(format!("Lecture impossible : {}", e)))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Config(format!("Parsing impossible : {}", e)))
+}
+
+pub fn save_phrases(app_data_dir: &Path, phrases: &PhantomPhrases) -> AppResult<()> {
+    std::fs::create_dir_all(app_data_dir)?;
+    let json = serde_json::to_string_pretty(phrases)
+        .map_err(|e| AppError::Config(format!("Sérialisation impossible : {}", e)))?;
+    std::fs::write(app_data_dir.join(PHRASES_FILE), json)?;
+    Ok(())
+}
+
+/// Lowercase, punctuation replaced by spaces, whitespace collapsed.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .flat_map(|c| c.to_lowercase())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Collapse n-grams repeated back to back more than twice into a single
+/// occurrence ("the end the end the end the end" -> "the end").
+/// Two occurrences are kept: "very very" is ordinary speech.
+fn collapse_repetitions(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+    let mut out: Vec<&str> = Vec::with_capacity(words.len());
+    let mut i = 0;
+
+    while i < words.len() {
+        let mut collapsed = false;
+        for n in (1..=MAX_NGRAM).rev() {
+            if i + n * 3 > words.len() {
+                continue;
+            }
+            let mut repeats = 1;
+            while i + n * (repeats + 1) <= words.len()
+                && keys[i..i + n] == keys[i + n * repeats..i + n * (repeats + 1)]
+            {
+                repeats += 1;
+            }
+            if repeats >= 3 {
+                log::info!(
+                    "Filtre : boucle de répétition retirée ({} x \"{}\")",
+                    repeats,
+                    words[i..i + n].join(" ")
+                );
+                out.extend_from_slice(&words[i..i + n]);
+                i += n * repeats;
+                collapsed = true;
+                break;
+            }
+        }
+        if !collapsed {
+            out.push(words[i]);
+            i += 1;
+        }
+    }
+
+    out.join(" ")
+}
+
+/// RMS level of the 16 kHz audio under a segment, if the span is known.
+fn span_rms(audio: &[f32], seg: &Segment) -> Option<f32> {
+    let start = (seg.start_ms.max(0) as usize * 16).min(audio.len());
+    let end = (seg.end_ms.max(0) as usize * 16).min(audio.len());
+    if end <= start {
+        return None;
+    }
+    let span = &audio[start..end];
+    let sum_sq: f32 = span.iter().map(|s| s * s).sum();
+    Some((sum_sq / span.len() as f32).sqrt())
+}
+
+/// Apply the filter stage to a transcription result. Every segment dropped or
+/// rewritten is logged with the reason.
+///
+/// whisper-rs does not expose the per-segment no-speech probability, so
+/// segments are judged silent from the RMS level of the audio under them
+/// (`FilterConfig::silence_rms_threshold`).
+pub fn filter(
+    result: TranscriptionResult,
+    audio: &[f32],
+    config: &FilterConfig,
+    phrases: &PhantomPhrases,
+) -> TranscriptionResult {
+    if !config.enabled {
+        return result;
+    }
+
+    let language = result.language.clone();
+    let phantoms: Vec<String> = phrases
+        .iter()
+        .filter(|(lang, _)| lang.as_str() == ANY_LANGUAGE || **lang == language || language == "auto")
+        .flat_map(|(_, list)| list.iter().map(|p| normalize(p)))
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    let mut changed = false;
+    let mut kept: Vec<Segment> = Vec::with_capacity(result.segments.len());
+
+    for mut seg in result.segments {
+        if let Some(rms) = span_rms(audio, &seg).filter(|rms| *rms < config.silence_rms_threshold) {
+            log::info!(
+                "Filtre : segment ignoré (audio silencieux, RMS {:.4}) : \"{}\"",
+                rms,
+                seg.text
+            );
+            changed = true;
+            continue;
+        }
+
+        let normalized = normalize(&seg.text);
+        if config.drop_phantom_phrases && phantoms.contains(&normalized) {
+            log::info!("Filtre : phrase fantôme retirée : \"{}\"", seg.text);
+            changed = true;
+            continue;
+        }
+
+        if config.collapse_repetitions {
+            if kept.last().map(|prev| normalize(&prev.text) == normalized).unwrap_or(false) {
+                log::info!("Filtre : segment répété retiré : \"{}\"", seg.text);
+                changed = true;
+                continue;
+            }
+
+            let collapsed = collapse_repetitions(&seg.text);
+            if collapsed != seg.text.split_whitespace().collect::<Vec<_>>().join(" ") {
+                seg.text = collapsed;
+                changed = true;
+            }
+        }
+
+        kept.push(seg);
+    }
+
+    // Leave Whisper's own spacing untouched when nothing was filtered
+    let text = if changed {
+        kept.iter()
+            .map(|s| s.text.as_str())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        result.text
+    };
+
+    TranscriptionResult {
+        text,
+        segments: kept,
+        ..result
+    }
+}
+
+/// Load the phrase list from `app_data_dir` and filter `result`.
+pub fn apply(
+    result: TranscriptionResult,
+    audio: &[f32],
+    config: &FilterConfig,
+    app_data_dir: &Path,
+) -> TranscriptionResult {
+    if !config.enabled {
+        return result;
+    }
+
+    let phrases = load_phrases(app_data_dir).unwrap_or_else(|e| {
+        log::error!("Liste des phrases fantômes illisible : {}", e);
+        default_phrases()
+    });
+    filter(result, audio, config, &phrases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One second of 16 kHz audio per segment, loud enough to pass the
+    /// silence check.
+    fn speech(secs: usize) -> Vec<f32> {
+        (0..secs * 16_000).map(|i| if i % 2 == 0 { 0.05 } else { -0.05 }).collect()
+    }
+
+    fn result(language: &str, texts: &[&str]) -> TranscriptionResult {
+        let segments: Vec<Segment> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Segment {
+                start_ms: i as i64 * 1000,
+                end_ms: (i as i64 + 1) * 1000,
+                text: text.to_string(),
+                confidence: 1.0,
+                speaker: None,
+            })
+            .collect();
+        TranscriptionResult {
+            text: texts.join(" "),
+            duration_ms: segments.len() as i64 * 1000,
+            segments,
+            language: language.to_string(),
+        }
+    }
+
+    fn texts(result: &TranscriptionResult) -> Vec<&str> {
+        result.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn phantom_phrases_are_dropped_for_their_language() {
+        let config = FilterConfig::default();
+        let phrases = default_phrases();
+        let input = ["Hello there.", "Thank you for watching!", "Abonnez-vous"];
+
+        let en = filter(result("en", &input), &speech(3), &config, &phrases);
+        assert_eq!(texts(&en), ["Hello there.", "Abonnez-vous"]);
+        assert_eq!(en.text, "Hello there. Abonnez-vous");
+
+        let fr = filter(result("fr", &input), &speech(3), &config, &phrases);
+        assert_eq!(texts(&fr), ["Hello there.", "Thank you for watching!"]);
+
+        let auto = filter(result("auto", &input), &speech(3), &config, &phrases);
+        assert_eq!(texts(&auto), ["Hello there."]);
+    }
+
+    #[test]
+    fn phantom_phrase_inside_a_sentence_is_kept() {
+        let text = "I said thanks for watching the kids";
+        let filtered = filter(
+            result("en", &[text]),
+            &speech(1),
+            &FilterConfig::default(),
+            &default_phrases(),
+        );
+        assert_eq!(filtered.text, text);
+    }
+
+    #[test]
+    fn loops_of_three_or_more_collapse() {
+        assert_eq!(collapse_repetitions("the end the end the end the end"), "the end");
+        assert_eq!(collapse_repetitions("no no no, stop"), "no stop");
+        assert_eq!(collapse_repetitions("Go go GO."), "Go");
+    }
+
+    #[test]
+    fn double_words_are_kept() {
+        assert_eq!(collapse_repetitions("it is very very cold"), "it is very very cold");
+        assert_eq!(collapse_repetitions("bye bye, see you"), "bye bye, see you");
+    }
+
+    #[test]
+    fn loops_longer_than_eight_words_are_kept() {
+        let eight = "one two three four five six seven eight";
+        assert_eq!(collapse_repetitions(&[eight; 3].join(" ")), eight);
+
+        let nine = "one two three four five six seven eight nine";
+        let text = [nine; 3].join(" ");
+        assert_eq!(collapse_repetitions(&text), text);
+    }
+
+    #[test]
+    fn repeated_segment_is_dropped() {
+        let filtered = filter(
+            result("en", &["Okay, let's start.", "Okay, let's start!", "First point."]),
+            &speech(3),
+            &FilterConfig::default(),
+            &PhantomPhrases::new(),
+        );
+        assert_eq!(texts(&filtered), ["Okay, let's start.", "First point."]);
+    }
+
+    #[test]
+    fn segment_over_silence_is_dropped() {
+        let mut audio = speech(3);
+        audio[16_000..32_000].fill(0.001);
+        let filtered = filter(
+            result("en", &["Real speech.", "Invented words.", "More speech."]),
+            &audio,
+            &FilterConfig::default(),
+            &PhantomPhrases::new(),
+        );
+        assert_eq!(texts(&filtered), ["Real speech.", "More speech."]);
+    }
+
+    #[test]
+    fn zero_threshold_disables_the_silence_check() {
+        let config = FilterConfig {
+            silence_rms_threshold: 0.0,
+            ..FilterConfig::default()
+        };
+        let filtered = filter(
+            result("en", &["Quiet words."]),
+            &vec![0.0; 16_000],
+            &config,
+            &PhantomPhrases::new(),
+        );
+        assert_eq!(texts(&filtered), ["Quiet words."]);
+    }
+
+    #[test]
+    fn unchanged_result_keeps_whisper_spacing() {
+        let mut input = result("en", &["Hello", "world"]);
+        input.text = " Hello  world".to_string();
+        let filtered = filter(input, &speech(2), &FilterConfig::default(), &default_phrases());
+        assert_eq!(filtered.text, " Hello  world");
+    }
+}
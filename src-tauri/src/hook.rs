@@ -0,0 +1,186 @@
+//! User-provided executable run on each dictation: the text is written to its
+//! stdin and its stdout replaces the text.
+
+use std::io::{ErrorKind, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use crate::config::HookConfig;
+use crate::errors::{AppError, AppResult};
+
+/// How long output is still collected once the hook has exited. What it wrote
+/// is already in the pipe; a background process it started may hold the pipe
+/// open for much longer.
+const DRAIN_GRACE: Duration = Duration::from_millis(200);
+
+/// Dictation details passed to the hook as environment variables.
+pub struct HookContext<'a> {
+    pub language: &'a str,
+    pub model: &'a str,
+    pub duration_ms: i64,
+}
+
+pub fn validate(config: &HookConfig) -> AppResult<()> {
+    if config.enabled && config.command.trim().is_empty() {
+        return Err(AppError::Hook("Aucune commande configurée".to_string()));
+    }
+    if config.timeout_secs == 0 {
+        return Err(AppError::Hook("Délai d'attente nul".to_string()));
+    }
+    Ok(())
+}
+
+/// Read `pipe` on a thread, forwarding each chunk until end of file.
+fn read_chunks(mut pipe: impl Read + Send + 'static) -> Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 8192];
+        loop {
+            match pipe.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if tx.send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    });
+    rx
+}
+
+/// Chunks received until the pipe closes or `deadline` passes.
+fn drain(chunks: &Receiver<Vec<u8>>, deadline: Instant) -> Vec<u8> {
+    let mut out = Vec::new();
+    while let Ok(chunk) = chunks.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        out.extend(chunk);
+    }
+    out
+}
+
+/// Pipe `text` through the configured command and return its output.
+pub fn run(text: &str, config: &HookConfig, context: &HookContext) -> AppResult<String> {
+    validate(config)?;
+
+    let mut child = Command::new(&config.command)
+        .args(&config.args)
+        .env("LOCALWHISPER_LANGUAGE", context.language)
+        .env("LOCALWHISPER_MODEL", context.model)
+        .env("LOCALWHISPER_DURATION_MS", context.duration_ms.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Hook(format!("Lancement de {} impossible : {}", config.command, e)))?;
+
+    // Write and read on separate threads so a chatty hook cannot fill a pipe
+    // and block both sides
+    let mut stdin = child.stdin.take().expect("stdin redirige");
+    let input = text.as_bytes().to_vec();
+    std::thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+    let stdout = read_chunks(child.stdout.take().expect("stdout redirige"));
+    let stderr = read_chunks(child.stderr.take().expect("stderr redirige"));
+
+    let deadline = Instant::now() + Duration::from_secs(config.timeout_secs);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AppError::Hook(format!(
+                "{} n'a pas répondu en {} s",
+                config.command, config.timeout_secs
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    // Not until end of file: a background process started by the hook
+    // (`cmd &`) inherits the pipes and may never close them
+    let drain_until = Instant::now() + DRAIN_GRACE;
+    let stdout = drain(&stdout, drain_until);
+    let stderr = drain(&stderr, drain_until);
+
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        return Err(AppError::Hook(format!(
+            "{} a échoué ({}) : {}",
+            config.command,
+            status,
+            stderr.trim()
+        )));
+    }
+
+    let output = String::from_utf8(stdout)
+        .map_err(|_| AppError::Hook("Sortie non UTF-8".to_string()))?;
+    Ok(output.trim_end_matches(['\n', '\r']).to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str, timeout_secs: u64) -> HookConfig {
+        HookConfig {
+            enabled: true,
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            timeout_secs,
+        }
+    }
+
+    fn context() -> HookContext<'static> {
+        HookContext {
+            language: "fr",
+            model: "ggml-small",
+            duration_ms: 1234,
+        }
+    }
+
+    #[test]
+    fn output_replaces_the_text() {
+        let config = HookConfig {
+            enabled: true,
+            command: "cat".to_string(),
+            ..HookConfig::default()
+        };
+        assert_eq!(run("Bonjour à tous\n", &config, &context()).unwrap(), "Bonjour à tous");
+    }
+
+    #[test]
+    fn dictation_details_are_in_the_environment() {
+        let config = shell(
+            r#"printf '%s %s %s' "$LOCALWHISPER_LANGUAGE" "$LOCALWHISPER_MODEL" "$LOCALWHISPER_DURATION_MS""#,
+            10,
+        );
+        assert_eq!(run("", &config, &context()).unwrap(), "fr ggml-small 1234");
+    }
+
+    #[test]
+    fn failing_hook_is_an_error() {
+        let err = run("text", &shell("echo broken >&2; exit 3", 10), &context()).unwrap_err();
+        assert!(err.to_string().contains("broken"));
+    }
+
+    #[test]
+    fn slow_hook_is_killed() {
+        let started = Instant::now();
+        assert!(run("text", &shell("sleep 30", 1), &context()).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn background_process_does_not_hold_the_dictation() {
+        let started = Instant::now();
+        let output = run("text", &shell("cat; sleep 30 &", 10), &context()).unwrap();
+        assert_eq!(output, "text");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -1,14 +1,31 @@
+use chrono::Local;
 use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState, Shortcut};
 
+use crate::active_window::{self, ActiveWindow};
 use crate::audio;
 use crate::clipboard;
+use crate::config::{AppConfig, OutputMode};
+use crate::continuation::{self, Insertion};
+use crate::engine::TranscriptionEngine;
 use crate::errors::{AppError, AppResult};
-use crate::models;
+use crate::hallucination;
+use crate::hook;
+use crate::normalization;
+use crate::postprocess;
+use crate::profiles;
+use crate::redaction::{self, RedactionWarning};
+use crate::rewrite;
+use crate::sinks::{self, SinkContext, SinkError};
+use crate::snippets;
 use crate::sounds;
 use crate::state::{AppState, RecordingStream};
-use crate::transcription;
+use crate::transcription::{self, TranscribeOptions, TranscriptionResult};
 use crate::tray;
+use crate::typing;
+use crate::undo;
+use crate::voice_commands;
 
 /// On Windows, replace "Super" modifier with "Ctrl" since the Win key
 /// is intercepted by the OS for most key combinations.
@@ -67,16 +84,44 @@ pub fn register_ptt_hotkey(app: &AppHandle, hotkey_str: &str) -> AppResult<()> {
     Ok(())
 }
 
-/// Register both hotkeys from the current config
+/// Register the undo-last-dictation hotkey
+pub fn register_undo_hotkey(app: &AppHandle, hotkey_str: &str) -> AppResult<()> {
+    if hotkey_str.is_empty() {
+        return Ok(());
+    }
+
+    let normalized = normalize_hotkey(hotkey_str);
+    let shortcut: Shortcut = normalized
+        .parse()
+        .map_err(|e| AppError::Hotkey(format!("Raccourci d'annulation invalide '{}' : {}", normalized, e)))?;
+
+    let handle = app.clone();
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                undo::trigger(&handle);
+            }
+        })
+        .map_err(|e| AppError::Hotkey(format!("Enregistrement raccourci d'annulation impossible : {}", e)))?;
+
+    Ok(())
+}
+
+/// Register all hotkeys from the current config
 pub fn register_all(app: &AppHandle) -> AppResult<()> {
     let state: tauri::State<AppState> = app.state();
-    let (hotkey, hotkey_ptt) = {
+    let (hotkey, hotkey_ptt, hotkey_undo) = {
         let inner = state.inner.lock().unwrap();
-        (inner.config.hotkey.clone(), inner.config.hotkey_ptt.clone())
+        (
+            inner.config.hotkey.clone(),
+            inner.config.hotkey_ptt.clone(),
+            inner.config.hotkey_undo.clone(),
+        )
     };
 
     register_hotkey(app, &hotkey)?;
     register_ptt_hotkey(app, &hotkey_ptt)?;
+    register_undo_hotkey(app, &hotkey_undo)?;
 
     Ok(())
 }
@@ -129,15 +174,22 @@ fn toggle_recording(app: &AppHandle) {
 }
 
 fn start(app: &AppHandle, state: &AppState, rec: &RecordingStream) {
+    // Before the recording starts, while the target application still has the focus
+    let window = active_window::detect();
+    if let Some(window) = &window {
+        log::info!("Fenêtre active : {} ({})", window.class, window.instance);
+    }
+
     let device = {
-        let inner = state.inner.lock().unwrap();
+        let mut inner = state.inner.lock().unwrap();
+        inner.target_window = window;
         inner.config.audio_device.clone()
     };
 
     match audio::start_recording(state, rec, device) {
         Ok(()) => {
             // Play start sound in a background thread to not block
-            std::thread::spawn(|| sounds::play_start_sound());
+            std::thread::spawn(sounds::play_start_sound);
 
             tray::update_tray_icon(app, true);
             let _ = app.emit("recording-state-changed", true);
@@ -154,7 +206,7 @@ fn stop(app: &AppHandle, state: &AppState, rec: &RecordingStream) {
     match audio::stop_recording(state, rec) {
         Ok(audio_data) => {
             // Play stop sound in a background thread
-            std::thread::spawn(|| sounds::play_stop_sound());
+            std::thread::spawn(sounds::play_stop_sound);
 
             tray::update_tray_icon(app, false);
             let _ = app.emit("recording-state-changed", false);
@@ -189,30 +241,51 @@ fn run_transcription(
     let _ = app.emit("transcription-started", ());
     tray::start_processing_animation(&app);
 
-    let (ctx, language, auto_paste, app_data_dir) = {
+    let (global_config, app_data_dir, window) = {
         let inner = inner_arc.lock().unwrap();
         (
-            inner.whisper_ctx.clone(),
-            inner.config.language.clone(),
-            inner.config.auto_paste,
+            inner.config.clone(),
             inner.app_data_dir.clone(),
+            inner.target_window.clone(),
         )
     };
-
-    // Resolve the Whisper context: use cached or lazy-load from disk
-    let ctx = match ctx {
-        Some(c) => c,
-        None => match resolve_model(&app, &inner_arc, &app_data_dir) {
-            Some(c) => c,
-            None => {
-                tray::update_tray_icon(&app, false);
-                return;
-            }
-        },
+    let profile = window
+        .as_ref()
+        .and_then(|w| profiles::find(&global_config.profiles, w));
+    let config = match profile {
+        Some(profile) => {
+            log::info!("Profil appliqué : {}", profile.name);
+            profiles::apply(&global_config, profile)
+        }
+        None => global_config.clone(),
+    };
+    let filter_config = &config.filter;
+
+    // A profile model other than the active one gets its own cached engine
+    let profile_model = config
+        .active_model
+        .as_deref()
+        .filter(|&model| global_config.active_model.as_deref() != Some(model));
+
+    // Resolve the engine: use cached or lazy-load from disk
+    let Some(engine) = resolve_model(&app, &inner_arc, &app_data_dir, profile_model) else {
+        tray::update_tray_icon(&app, false);
+        return;
     };
 
-    match transcription::transcribe(&ctx, &audio_data, &language) {
-        Ok(text) => {
+    let options = TranscribeOptions {
+        language: config.language.clone(),
+        timestamps: false,
+        diarize: false,
+    };
+    let result = engine
+        .transcribe(&audio_data, &options, None)
+        .map(|r| hallucination::apply(r, &audio_data, filter_config, &app_data_dir));
+
+    match result {
+        Ok(result) => {
+            let rule_set = profile.and_then(|p| p.text_rules.as_deref());
+            let text = process_text(&app, &result, &config, rule_set, &app_data_dir);
             if text.is_empty() {
                 tray::update_tray_icon(&app, false);
                 let _ = app.emit("transcription-complete", "");
@@ -221,9 +294,42 @@ fn run_transcription(
 
             log::info!("Transcription : {}", text);
 
-            match clipboard::copy_and_paste(&app, &text, auto_paste) {
+            let previous = inner_arc.lock().unwrap().insertions.last().cloned();
+            let inserted = continuation::join(
+                &text,
+                &result.language,
+                previous.as_ref(),
+                window.as_ref(),
+                &config.continuation,
+            );
+            let output = match config.output_mode {
+                OutputMode::Paste => clipboard::copy_and_paste(&app, &inserted, &config),
+                OutputMode::Type => typing::type_text(&app, &inserted, &config.typing, config.input_backend),
+                OutputMode::SinksOnly => Ok(()),
+            };
+            // Only text that reached the window can be continued. Without
+            // auto-paste the user may paste it anywhere
+            let reached_window = match config.output_mode {
+                OutputMode::Paste => config.auto_paste,
+                OutputMode::Type => true,
+                OutputMode::SinksOnly => false,
+            };
+            {
+                let mut inner = inner_arc.lock().unwrap();
+                if output.is_ok() && reached_window {
+                    inner.insertions.push(Insertion::new(&inserted, window.as_ref()));
+                    let excess = inner.insertions.len().saturating_sub(config.undo_depth.max(1));
+                    inner.insertions.drain(..excess);
+                } else {
+                    // What the window now holds is unknown
+                    inner.insertions.clear();
+                }
+            }
+
+            send_to_sinks(&app, &text, &config, &result.language, window.clone());
+            match output {
                 Ok(()) => {
-                    std::thread::spawn(|| sounds::play_complete_sound());
+                    std::thread::spawn(sounds::play_complete_sound);
                     let _ = app.emit("transcription-complete", &text);
                 }
                 Err(e) => {
@@ -241,48 +347,186 @@ fn run_transcription(
     tray::update_tray_icon(&app, false);
 }
 
-fn resolve_model(
+/// Sinks run on their own thread, after the paste, so a slow webhook does
+/// not hold up the dictation.
+fn send_to_sinks(
     app: &AppHandle,
-    inner_arc: &std::sync::Arc<std::sync::Mutex<crate::state::InnerState>>,
-    app_data_dir: &std::path::PathBuf,
-) -> Option<std::sync::Arc<whisper_rs::WhisperContext>> {
-    let model_id = {
-        let inner = inner_arc.lock().unwrap();
-        inner.config.active_model.clone()
+    text: &str,
+    config: &AppConfig,
+    language: &str,
+    window: Option<ActiveWindow>,
+) {
+    if !config.sinks.iter().any(|s| s.enabled) {
+        return;
+    }
+    let app = app.clone();
+    let text = text.to_string();
+    let sinks = config.sinks.clone();
+    let language = language.to_string();
+    let model = config.active_model.clone().unwrap_or_default();
+    let time = Local::now();
+
+    std::thread::spawn(move || {
+        let context = SinkContext {
+            language: &language,
+            model: &model,
+            window: window.as_ref(),
+            time,
+        };
+        let report = |error: SinkError| {
+            log::error!("Sortie {} en échec : {}", error.sink, error.message);
+            let _ = app.emit("sink-error", error);
+        };
+        tauri::async_runtime::block_on(sinks::dispatch(&sinks, &text, &context, &report));
+    });
+}
+
+/// Text stages that run on this machine, in order. `clipboard` fills the
+/// `{clipboard}` snippet variable and `warn` receives redaction warnings.
+fn local_stages(
+    text: &str,
+    language: &str,
+    config: &AppConfig,
+    rule_set: Option<&str>,
+    app_data_dir: &std::path::Path,
+    clipboard: &dyn Fn() -> Option<String>,
+    warn: &dyn Fn(RedactionWarning),
+) -> String {
+    // Before voice commands, so "trois virgule cinq" is read as a number
+    let mut text = normalization::normalize(text, language, &config.normalization);
+    if config.voice_commands {
+        text = voice_commands::apply(&text, language, app_data_dir);
+    }
+    let text = postprocess::apply(&text, language, rule_set, app_data_dir);
+    // After rules, so they do not reformat the inserted templates
+    let text = snippets::apply(&text, app_data_dir, clipboard);
+    // After every local stage, and before the text can leave through the LLM
+    redaction::redact(&text, &config.redaction, warn)
+}
+
+/// Text stages run on a dictation between transcription and paste.
+fn process_text(
+    app: &AppHandle,
+    result: &TranscriptionResult,
+    config: &AppConfig,
+    rule_set: Option<&str>,
+    app_data_dir: &std::path::Path,
+) -> String {
+    let language = result.language.as_str();
+    let text = local_stages(
+        &result.text,
+        language,
+        config,
+        rule_set,
+        app_data_dir,
+        &|| app.clipboard().read_text().ok(),
+        &|warning| {
+            log::warn!("Donnée personnelle détectée : {}", warning.kind);
+            let _ = app.emit("redaction-warning", warning);
+        },
+    );
+
+    // On failure the optional stages below pass the text through unchanged
+    let fallback = |text: String, e: AppError| {
+        log::error!("{}", e);
+        let _ = app.emit("error", e.to_string());
+        text
     };
 
-    let model_id = match model_id {
-        Some(id) => id,
-        None => {
-            let _ = app.emit(
-                "error",
-                "Aucun modele selectionne. Veuillez configurer un modele dans les parametres.",
-            );
-            return None;
+    let text = if config.rewrite.enabled && !text.is_empty() {
+        match tauri::async_runtime::block_on(rewrite::rewrite(&text, &config.rewrite)) {
+            Ok(rewritten) => rewritten,
+            Err(e) => fallback(text, e),
         }
+    } else {
+        text
     };
 
-    let path = match models::get_model_path(app_data_dir, &model_id) {
-        Some(p) => p,
-        None => {
-            let _ = app.emit(
-                "error",
-                "Aucun modele installe. Veuillez telecharger un modele dans les parametres.",
-            );
-            return None;
-        }
+    if !config.hook.enabled || text.is_empty() {
+        return text;
+    }
+    let context = hook::HookContext {
+        language,
+        model: config.active_model.as_deref().unwrap_or(""),
+        duration_ms: result.duration_ms,
     };
+    match hook::run(&text, &config.hook, &context) {
+        Ok(output) => output,
+        Err(e) => fallback(text, e),
+    }
+}
 
-    match transcription::load_model(&path) {
-        Ok(ctx) => {
-            let mut inner = inner_arc.lock().unwrap();
-            inner.whisper_ctx = Some(ctx.clone());
-            Some(ctx)
-        }
+fn resolve_model(
+    app: &AppHandle,
+    inner_arc: &std::sync::Arc<std::sync::Mutex<crate::state::InnerState>>,
+    app_data_dir: &std::path::Path,
+    profile_model: Option<&str>,
+) -> Option<std::sync::Arc<dyn TranscriptionEngine>> {
+    let engine = match profile_model {
+        Some(model_id) => transcription::ensure_profile_model(inner_arc, app_data_dir, model_id),
+        None => transcription::ensure_model(inner_arc, app_data_dir),
+    };
+    match engine {
+        Ok(engine) => Some(engine),
         Err(e) => {
             log::error!("Chargement modele impossible : {}", e);
-            let _ = app.emit("error", format!("Erreur de chargement du modele : {}", e));
+            let _ = app.emit("error", e.to_string());
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RedactionAction;
+    use crate::engine::MockEngine;
+
+    fn data_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("localwhisper-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn dictate(transcript: &str, language: &str) -> TranscriptionResult {
+        let engine = MockEngine::new(vec![transcript.to_string()]);
+        let options = TranscribeOptions {
+            language: language.to_string(),
+            timestamps: false,
+            diarize: false,
+        };
+        engine.transcribe(&[0.0; 16000], &options, None).unwrap()
+    }
+
+    fn run(result: &TranscriptionResult, config: &AppConfig, dir: &std::path::Path) -> String {
+        local_stages(&result.text, &result.language, config, None, dir, &|| None, &|_| {})
+    }
+
+    #[test]
+    fn voice_commands_and_snippets_run_before_redaction() {
+        let dir = data_dir("stages");
+        snippets::add_snippet(&dir, "insert my number".into(), "06 12 34 56 78".into()).unwrap();
+        let mut config = AppConfig {
+            voice_commands: true,
+            ..AppConfig::default()
+        };
+        config.redaction.enabled = true;
+        config.redaction.phones = RedactionAction::Placeholder;
+
+        let result = dictate("Call me back new line insert my number", "en");
+        assert_eq!(run(&result, &config, &dir), "Call me back\n[PHONE]");
+    }
+
+    #[test]
+    fn numbers_are_normalized_before_voice_commands() {
+        let dir = data_dir("numbers");
+        let config = AppConfig {
+            voice_commands: true,
+            ..AppConfig::default()
+        };
+
+        let result = dictate("Il reste trois virgule cinq litres point final", "fr");
+        assert_eq!(run(&result, &config, &dir), "Il reste 3,5 litres.");
+    }
+}
@@ -0,0 +1,461 @@
+//! Keyboard injection backends used to paste or type a dictation.
+//!
+//! enigo (XTest) only reaches X11 and XWayland windows, so Wayland sessions
+//! go through wtype, ydotool or a uinput virtual keyboard instead. In `auto`
+//! mode the backends suited to the session are tried in order until one
+//! succeeds.
+
+use serde::Serialize;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::active_window;
+use crate::chord::{self, Chord, ChordKey, Modifier};
+use crate::config::InputBackend;
+use crate::errors::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Session {
+    X11,
+    Wayland,
+    /// macOS, Windows, or no graphical session detected
+    Other,
+}
+
+pub fn detect_session() -> Session {
+    if !cfg!(target_os = "linux") {
+        return Session::Other;
+    }
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    match var("XDG_SESSION_TYPE").as_str() {
+        "wayland" => Session::Wayland,
+        "x11" => Session::X11,
+        _ if !var("WAYLAND_DISPLAY").is_empty() => Session::Wayland,
+        _ if !var("DISPLAY").is_empty() => Session::X11,
+        _ => Session::Other,
+    }
+}
+
+/// What to send to the focused window.
+pub enum Action<'a> {
+    /// `chord` pressed `times` times in a row
+    Chord { chord: &'a Chord, times: usize },
+    Text { text: &'a str, char_delay: Duration },
+}
+
+trait Injector {
+    fn backend(&self) -> InputBackend;
+    fn available(&self) -> bool;
+    fn inject(&self, app: &AppHandle, action: &Action) -> AppResult<()>;
+}
+
+fn failed(backend: &str, e: impl std::fmt::Display) -> AppError {
+    AppError::Clipboard(format!("{} : {}", backend, e))
+}
+
+pub fn in_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+fn run_tool(program: &str, args: &[String]) -> AppResult<()> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| failed(program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(failed(program, format!("{} {}", output.status, stderr.trim())));
+    }
+    Ok(())
+}
+
+/// enigo: XTest on X11, the system APIs on macOS and Windows.
+struct Native;
+
+impl Native {
+    fn type_text(text: &str, char_delay: Duration) -> AppResult<()> {
+        use enigo::{Enigo, Keyboard, Settings};
+
+        let mut enigo = Enigo::new(&Settings::default()).map_err(|e| failed("enigo", e))?;
+        if char_delay.is_zero() {
+            return enigo.text(text).map_err(|e| failed("enigo", e));
+        }
+        let mut buf = [0u8; 4];
+        for c in text.chars() {
+            enigo
+                .text(c.encode_utf8(&mut buf))
+                .map_err(|e| failed("enigo", e))?;
+            std::thread::sleep(char_delay);
+        }
+        Ok(())
+    }
+
+    fn send_chord(chord: &Chord, times: usize) -> AppResult<()> {
+        use enigo::{Enigo, Settings};
+
+        let mut enigo = Enigo::new(&Settings::default()).map_err(|e| failed("enigo", e))?;
+        for _ in 0..times {
+            chord::send(&mut enigo, chord)?;
+        }
+        Ok(())
+    }
+}
+
+impl Injector for Native {
+    fn backend(&self) -> InputBackend {
+        InputBackend::Native
+    }
+
+    fn available(&self) -> bool {
+        !cfg!(target_os = "linux") || std::env::var_os("DISPLAY").is_some()
+    }
+
+    fn inject(&self, app: &AppHandle, action: &Action) -> AppResult<()> {
+        let job: Box<dyn FnOnce() -> AppResult<()> + Send> = match action {
+            Action::Chord { chord, times } => {
+                let (chord, times) = ((*chord).clone(), *times);
+                Box::new(move || Native::send_chord(&chord, times))
+            }
+            Action::Text { text, char_delay } => {
+                let (text, char_delay) = (text.to_string(), *char_delay);
+                Box::new(move || Native::type_text(&text, char_delay))
+            }
+        };
+
+        // enigo must run on the main thread on macOS (HIToolbox / TSM APIs
+        // require it). Wait for the result so failures can fall through to
+        // the next backend.
+        let (tx, rx) = mpsc::channel();
+        app.run_on_main_thread(move || {
+            let _ = tx.send(job());
+        })
+        .map_err(|e| AppError::Clipboard(format!("Dispatch main thread impossible : {}", e)))?;
+        rx.recv()
+            .map_err(|_| AppError::Clipboard("Simulation clavier interrompue".to_string()))?
+    }
+}
+
+/// wtype: Wayland virtual-keyboard protocol (wlroots compositors, KDE).
+struct Wtype;
+
+impl Wtype {
+    fn modifier(modifier: Modifier) -> &'static str {
+        match modifier {
+            Modifier::Ctrl => "ctrl",
+            Modifier::Shift => "shift",
+            Modifier::Alt => "alt",
+            Modifier::Meta => "logo",
+        }
+    }
+
+    /// wtype uploads its own keymap, so keysym names do not depend on the
+    /// user's layout.
+    fn key(key: ChordKey) -> String {
+        match key {
+            ChordKey::Char(c) if c.is_ascii_alphanumeric() => c.to_string(),
+            // Unicode keysym, for punctuation and accented letters
+            ChordKey::Char(c) => format!("U{:04X}", c as u32),
+            ChordKey::Insert => "Insert".to_string(),
+            ChordKey::Delete => "Delete".to_string(),
+            ChordKey::Backspace => "BackSpace".to_string(),
+            ChordKey::Enter => "Return".to_string(),
+            ChordKey::Tab => "Tab".to_string(),
+            ChordKey::Space => "space".to_string(),
+        }
+    }
+}
+
+impl Injector for Wtype {
+    fn backend(&self) -> InputBackend {
+        InputBackend::Wtype
+    }
+
+    fn available(&self) -> bool {
+        std::env::var_os("WAYLAND_DISPLAY").is_some() && in_path("wtype")
+    }
+
+    fn inject(&self, _app: &AppHandle, action: &Action) -> AppResult<()> {
+        let mut args = Vec::new();
+        match action {
+            Action::Chord { chord, times } => {
+                for m in &chord.modifiers {
+                    args.extend(["-M".to_string(), Wtype::modifier(*m).to_string()]);
+                }
+                for _ in 0..*times {
+                    args.extend(["-k".to_string(), Wtype::key(chord.key)]);
+                }
+                for m in chord.modifiers.iter().rev() {
+                    args.extend(["-m".to_string(), Wtype::modifier(*m).to_string()]);
+                }
+            }
+            Action::Text { text, char_delay } => {
+                args.extend(["-d".to_string(), char_delay.as_millis().to_string()]);
+                args.extend(["--".to_string(), text.to_string()]);
+            }
+        }
+        run_tool("wtype", &args)
+    }
+}
+
+/// ydotool: kernel-level input through the ydotoold daemon, works on every
+/// compositor. Keys are given as evdev codes, which cannot follow the layout:
+/// chord keys are taken at their US QWERTY position.
+struct Ydotool;
+
+impl Injector for Ydotool {
+    fn backend(&self) -> InputBackend {
+        InputBackend::Ydotool
+    }
+
+    fn available(&self) -> bool {
+        in_path("ydotool")
+    }
+
+    fn inject(&self, _app: &AppHandle, action: &Action) -> AppResult<()> {
+        let args: Vec<String> = match action {
+            Action::Chord { chord, times } => {
+                let codes = chord.qwerty_evdev_codes()?;
+                let presses = codes.iter().map(|c| format!("{}:1", c));
+                let releases = codes.iter().rev().map(|c| format!("{}:0", c));
+                let once: Vec<String> = presses.chain(releases).collect();
+                std::iter::once("key".to_string())
+                    .chain(std::iter::repeat_n(once, *times).flatten())
+                    .collect()
+            }
+            Action::Text { text, char_delay } => vec![
+                "type".to_string(),
+                "--key-delay".to_string(),
+                char_delay.as_millis().to_string(),
+                "--".to_string(),
+                text.to_string(),
+            ],
+        };
+        run_tool("ydotool", &args)
+    }
+}
+
+/// Virtual keyboard created on /dev/uinput for each chord. Text cannot be
+/// typed this way: the keycodes would be read through the user's layout.
+struct Uinput;
+
+impl Injector for Uinput {
+    fn backend(&self) -> InputBackend {
+        InputBackend::Uinput
+    }
+
+    fn available(&self) -> bool {
+        cfg!(target_os = "linux")
+            && std::fs::OpenOptions::new()
+                .write(true)
+                .open("/dev/uinput")
+                .is_ok()
+    }
+
+    fn inject(&self, _app: &AppHandle, action: &Action) -> AppResult<()> {
+        match action {
+            Action::Chord { chord, times } => uinput::send_chord(chord, *times),
+            Action::Text { .. } => Err(failed(
+                "uinput",
+                "saisie de texte non supportée, seulement les raccourcis",
+            )),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod uinput {
+    use std::fs::File;
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+    use std::time::Duration;
+
+    use super::failed;
+    use crate::chord::Chord;
+    use crate::errors::AppResult;
+
+    const EV_SYN: u16 = 0;
+    const EV_KEY: u16 = 1;
+    const SYN_REPORT: u16 = 0;
+    const BUS_VIRTUAL: u16 = 0x06;
+
+    /// `_IOW('U', nr, size)` / `_IO('U', nr)` from linux/uinput.h
+    const fn iow(nr: u32, size: usize) -> libc::Ioctl {
+        ((1u32 << 30) | ((size as u32) << 16) | (0x55 << 8) | nr) as libc::Ioctl
+    }
+    const UI_SET_EVBIT: libc::Ioctl = iow(100, std::mem::size_of::<libc::c_int>());
+    const UI_SET_KEYBIT: libc::Ioctl = iow(101, std::mem::size_of::<libc::c_int>());
+    const UI_DEV_SETUP: libc::Ioctl = iow(3, std::mem::size_of::<libc::uinput_setup>());
+    const UI_DEV_CREATE: libc::Ioctl = (0x55 << 8 | 1) as libc::Ioctl;
+    const UI_DEV_DESTROY: libc::Ioctl = (0x55 << 8 | 2) as libc::Ioctl;
+
+    fn ioctl(file: &File, request: libc::Ioctl, arg: libc::c_ulong) -> AppResult<()> {
+        // SAFETY: valid fd, and `arg` is either an integer or a pointer to a
+        // live struct of the size encoded in `request`
+        if unsafe { libc::ioctl(file.as_raw_fd(), request, arg) } < 0 {
+            return Err(failed("uinput", std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    fn emit(file: &mut File, type_: u16, code: u16, value: i32) -> AppResult<()> {
+        // SAFETY: input_event is plain data; zeroed is a valid value
+        let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+        event.type_ = type_;
+        event.code = code;
+        event.value = value;
+        // SAFETY: reading the bytes of a fully initialised repr(C) struct
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &event as *const libc::input_event as *const u8,
+                std::mem::size_of::<libc::input_event>(),
+            )
+        };
+        file.write_all(bytes).map_err(|e| failed("uinput", e))
+    }
+
+    pub fn send_chord(chord: &Chord, times: usize) -> AppResult<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open("/dev/uinput")
+            .map_err(|e| failed("uinput", e))?;
+
+        let codes = chord.qwerty_evdev_codes()?;
+
+        ioctl(&file, UI_SET_EVBIT, EV_KEY as libc::c_ulong)?;
+        for code in &codes {
+            ioctl(&file, UI_SET_KEYBIT, *code as libc::c_ulong)?;
+        }
+
+        // SAFETY: plain data, zeroed is valid
+        let mut setup: libc::uinput_setup = unsafe { std::mem::zeroed() };
+        setup.id.bustype = BUS_VIRTUAL;
+        for (dst, src) in setup.name.iter_mut().zip(b"LocalWhisper keyboard") {
+            *dst = *src as libc::c_char;
+        }
+        ioctl(&file, UI_DEV_SETUP, &setup as *const _ as libc::c_ulong)?;
+        ioctl(&file, UI_DEV_CREATE, 0)?;
+
+        // Let the compositor pick up the new device before sending keys
+        std::thread::sleep(Duration::from_millis(200));
+
+        let mut result = Ok(());
+        for _ in 0..times {
+            for code in &codes {
+                result = result.and_then(|_| emit(&mut file, EV_KEY, *code, 1));
+            }
+            result = result.and_then(|_| emit(&mut file, EV_SYN, SYN_REPORT, 0));
+            for code in codes.iter().rev() {
+                result = result.and_then(|_| emit(&mut file, EV_KEY, *code, 0));
+            }
+            result = result.and_then(|_| emit(&mut file, EV_SYN, SYN_REPORT, 0));
+        }
+
+        // Give the events time to be read before the device disappears
+        std::thread::sleep(Duration::from_millis(50));
+        let _ = ioctl(&file, UI_DEV_DESTROY, 0);
+        result
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod uinput {
+    use crate::chord::Chord;
+    use crate::errors::AppResult;
+
+    pub fn send_chord(_chord: &Chord, _times: usize) -> AppResult<()> {
+        Err(super::failed("uinput", "Linux uniquement"))
+    }
+}
+
+/// Backends tried for `preference`, in order.
+fn candidates(preference: InputBackend) -> Vec<Box<dyn Injector>> {
+    match preference {
+        InputBackend::Native => vec![Box::new(Native)],
+        InputBackend::Wtype => vec![Box::new(Wtype)],
+        InputBackend::Ydotool => vec![Box::new(Ydotool)],
+        InputBackend::Uinput => vec![Box::new(Uinput)],
+        InputBackend::Auto => match detect_session() {
+            Session::Wayland => {
+                let mut injectors: Vec<Box<dyn Injector>> =
+                    vec![Box::new(Wtype), Box::new(Ydotool), Box::new(Uinput)];
+                // XTest only reaches XWayland windows, the only ones X11 can
+                // see; with a native Wayland window focused it would type
+                // into nothing
+                if active_window::detect().is_some() {
+                    injectors.push(Box::new(Native));
+                }
+                injectors
+            }
+            Session::X11 | Session::Other => vec![Box::new(Native)],
+        },
+    }
+}
+
+/// Send `action` through the first backend that accepts it. Blocks until it
+/// is done, so it must not be called from the main thread.
+pub fn inject(app: &AppHandle, preference: InputBackend, action: &Action) -> AppResult<()> {
+    let mut errors = Vec::new();
+
+    for injector in candidates(preference) {
+        let name = injector.backend().name();
+        if !injector.available() {
+            errors.push(format!("{} indisponible", name));
+            continue;
+        }
+        match injector.inject(app, action) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                log::warn!("Backend de saisie {} en échec : {}", name, e);
+                errors.push(e.to_string());
+            }
+        }
+    }
+
+    let session = detect_session();
+    let hint = if preference == InputBackend::Auto && session == Session::Wayland {
+        " ; installez wtype ou ydotool"
+    } else {
+        ""
+    };
+    Err(AppError::Clipboard(format!(
+        "Aucun backend de saisie n'a fonctionné (session {:?}) : {}{}",
+        session,
+        errors.join(" ; "),
+        hint
+    )))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendStatus {
+    pub backend: InputBackend,
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InjectionStatus {
+    pub session: Session,
+    pub backends: Vec<BackendStatus>,
+}
+
+pub fn status() -> InjectionStatus {
+    let all: [Box<dyn Injector>; 4] = [
+        Box::new(Native),
+        Box::new(Wtype),
+        Box::new(Ydotool),
+        Box::new(Uinput),
+    ];
+    InjectionStatus {
+        session: detect_session(),
+        backends: all
+            .iter()
+            .map(|i| BackendStatus {
+                backend: i.backend(),
+                available: i.available(),
+            })
+            .collect(),
+    }
+}
@@ -1,16 +1,39 @@
+mod active_window;
 mod audio;
+mod audio_file;
+mod benchmark;
+mod chord;
 mod clipboard;
 mod commands;
 mod config;
+mod continuation;
+mod engine;
 mod errors;
+mod export;
+mod file_transcription;
+mod hallucination;
+mod hook;
 mod hotkey;
+mod injection;
 mod models;
+mod normalization;
 mod permissions;
+mod postprocess;
+mod profiles;
+mod redaction;
+mod rewrite;
+mod segmentation;
+mod sinks;
+mod snippets;
 mod sounds;
 mod state;
 mod system_info;
 mod transcription;
 mod tray;
+mod typing;
+mod undo;
+mod voice_commands;
+mod watch_folder;
 
 use config::AppConfig;
 use state::{AppState, RecordingStream};
@@ -45,6 +68,8 @@ pub fn run() {
                 log::error!("Enregistrement raccourcis impossible : {}", e);
             }
 
+            watch_folder::start(app.handle());
+
             // Start hidden from dock (tray-only)
             #[cfg(target_os = "macos")]
             tray::set_dock_visible(false);
@@ -78,13 +103,50 @@ pub fn run() {
             commands::suspend_hotkey,
             commands::resume_hotkey,
             commands::update_hotkey_ptt,
+            commands::update_hotkey_undo,
+            commands::set_undo_depth,
+            commands::undo_last_dictation,
             commands::set_auto_paste,
             commands::set_language,
             commands::set_ui_locale,
             commands::set_audio_device,
+            commands::set_force_cpu,
+            commands::set_watch_folder,
+            commands::get_phantom_phrases,
+            commands::save_phantom_phrases,
+            commands::set_voice_commands,
+            commands::set_normalization,
+            commands::set_redaction,
+            commands::set_rewrite,
+            commands::test_rewrite,
+            commands::set_hook,
+            commands::test_hook,
+            commands::set_paste_chord,
+            commands::set_clipboard_restore,
+            commands::set_output_mode,
+            commands::set_input_backend,
+            commands::get_injection_status,
+            commands::set_sinks,
+            commands::set_continuation,
+            commands::test_sink,
+            commands::set_profiles,
+            commands::get_last_active_window,
+            commands::get_voice_commands,
+            commands::save_voice_commands,
+            commands::list_snippets,
+            commands::add_snippet,
+            commands::update_snippet,
+            commands::delete_snippet,
+            commands::get_text_rules,
+            commands::save_text_rules,
+            commands::test_text_rules,
             commands::test_microphone,
             commands::mark_setup_complete,
             commands::get_recording_state,
+            commands::transcribe_file,
+            commands::export_transcript,
+            commands::benchmark_models,
+            commands::get_benchmarks,
             commands::check_permissions,
             commands::request_microphone_permission,
             commands::open_accessibility_preferences,
@@ -1,11 +1,15 @@
 use futures_util::StreamExt;
 use serde::Serialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter};
 
 use crate::errors::{AppError, AppResult};
 
-const HF_BASE: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+const HF_BASE: &str = "https://huggingface.co";
+/// Standard ggml conversions of the OpenAI models.
+const WHISPER_CPP_REPO: &str = "ggerganov/whisper.cpp";
+/// tinydiarize fine-tunes, published apart from the standard models.
+const TINYDIARIZE_REPO: &str = "akashmjn/tinydiarize-whisper.cpp";
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ModelInfo {
@@ -16,6 +20,8 @@ pub struct ModelInfo {
     pub url: String,
     pub is_english_only: bool,
     pub is_quantized: bool,
+    /// Model fine-tuned with tinydiarize: emits speaker-turn markers
+    pub supports_diarization: bool,
     pub is_downloaded: bool,
 }
 
@@ -34,39 +40,52 @@ struct ModelDef {
     size_label: &'static str,
     english_only: bool,
     quantized: bool,
+    tdrz: bool,
+    /// Hugging Face repository the file is downloaded from
+    repo: &'static str,
 }
 
 const CATALOG: &[ModelDef] = &[
-    ModelDef { id: "ggml-tiny",          name: "Tiny",             size_bytes: 77_700_000,    size_label: "75 Mo",   english_only: false, quantized: false },
-    ModelDef { id: "ggml-tiny.en",       name: "Tiny (English)",   size_bytes: 77_700_000,    size_label: "75 Mo",   english_only: true,  quantized: false },
-    ModelDef { id: "ggml-tiny-q5_1",     name: "Tiny Q5",          size_bytes: 44_000_000,    size_label: "42 Mo",   english_only: false, quantized: true  },
-    ModelDef { id: "ggml-base",          name: "Base",             size_bytes: 147_000_000,   size_label: "142 Mo",  english_only: false, quantized: false },
-    ModelDef { id: "ggml-base.en",       name: "Base (English)",   size_bytes: 147_000_000,   size_label: "142 Mo",  english_only: true,  quantized: false },
-    ModelDef { id: "ggml-base-q5_1",     name: "Base Q5",          size_bytes: 90_000_000,    size_label: "87 Mo",   english_only: false, quantized: true  },
-    ModelDef { id: "ggml-small",         name: "Small",            size_bytes: 488_000_000,   size_label: "466 Mo",  english_only: false, quantized: false },
-    ModelDef { id: "ggml-small.en",      name: "Small (English)",  size_bytes: 488_000_000,   size_label: "466 Mo",  english_only: true,  quantized: false },
-    ModelDef { id: "ggml-small-q5_1",    name: "Small Q5",         size_bytes: 190_000_000,   size_label: "181 Mo",  english_only: false, quantized: true  },
-    ModelDef { id: "ggml-medium",        name: "Medium",           size_bytes: 1_533_000_000, size_label: "1.4 Go",  english_only: false, quantized: false },
-    ModelDef { id: "ggml-medium.en",     name: "Medium (English)", size_bytes: 1_533_000_000, size_label: "1.4 Go",  english_only: true,  quantized: false },
-    ModelDef { id: "ggml-medium-q5_0",   name: "Medium Q5",        size_bytes: 540_000_000,   size_label: "515 Mo",  english_only: false, quantized: true  },
-    ModelDef { id: "ggml-large-v3",      name: "Large v3",         size_bytes: 3_094_000_000, size_label: "2.9 Go",  english_only: false, quantized: false },
-    ModelDef { id: "ggml-large-v3-q5_0", name: "Large v3 Q5",      size_bytes: 1_100_000_000, size_label: "1.0 Go",  english_only: false, quantized: true  },
+    ModelDef { id: "ggml-tiny",          name: "Tiny",                      size_bytes: 77_700_000,    size_label: "75 Mo",  english_only: false, quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-tiny.en",       name: "Tiny (English)",            size_bytes: 77_700_000,    size_label: "75 Mo",  english_only: true,  quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-tiny-q5_1",     name: "Tiny Q5",                   size_bytes: 44_000_000,    size_label: "42 Mo",  english_only: false, quantized: true,  tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-base",          name: "Base",                      size_bytes: 147_000_000,   size_label: "142 Mo", english_only: false, quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-base.en",       name: "Base (English)",            size_bytes: 147_000_000,   size_label: "142 Mo", english_only: true,  quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-base-q5_1",     name: "Base Q5",                   size_bytes: 90_000_000,    size_label: "87 Mo",  english_only: false, quantized: true,  tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-small",         name: "Small",                     size_bytes: 488_000_000,   size_label: "466 Mo", english_only: false, quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-small.en",      name: "Small (English)",           size_bytes: 488_000_000,   size_label: "466 Mo", english_only: true,  quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-small.en-tdrz", name: "Small (English, speakers)", size_bytes: 488_000_000,   size_label: "466 Mo", english_only: true,  quantized: false, tdrz: true,  repo: TINYDIARIZE_REPO },
+    ModelDef { id: "ggml-small-q5_1",    name: "Small Q5",                  size_bytes: 190_000_000,   size_label: "181 Mo", english_only: false, quantized: true,  tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-medium",        name: "Medium",                    size_bytes: 1_533_000_000, size_label: "1.4 Go", english_only: false, quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-medium.en",     name: "Medium (English)",          size_bytes: 1_533_000_000, size_label: "1.4 Go", english_only: true,  quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-medium-q5_0",   name: "Medium Q5",                 size_bytes: 540_000_000,   size_label: "515 Mo", english_only: false, quantized: true,  tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-large-v3",      name: "Large v3",                  size_bytes: 3_094_000_000, size_label: "2.9 Go", english_only: false, quantized: false, tdrz: false, repo: WHISPER_CPP_REPO },
+    ModelDef { id: "ggml-large-v3-q5_0", name: "Large v3 Q5",               size_bytes: 1_100_000_000, size_label: "1.0 Go", english_only: false, quantized: true,  tdrz: false, repo: WHISPER_CPP_REPO },
 ];
 
-fn models_dir(app_data_dir: &PathBuf) -> PathBuf {
+fn models_dir(app_data_dir: &Path) -> PathBuf {
     app_data_dir.join("models")
 }
 
-fn model_file(app_data_dir: &PathBuf, model_id: &str) -> PathBuf {
+fn model_file(app_data_dir: &Path, model_id: &str) -> PathBuf {
     models_dir(app_data_dir).join(format!("{}.bin", model_id))
 }
 
-pub fn get_model_path(app_data_dir: &PathBuf, model_id: &str) -> Option<PathBuf> {
+pub fn get_model_path(app_data_dir: &Path, model_id: &str) -> Option<PathBuf> {
     let path = model_file(app_data_dir, model_id);
     path.exists().then_some(path)
 }
 
-pub fn list_models(app_data_dir: &PathBuf) -> Vec<ModelInfo> {
+fn download_url(def: &ModelDef) -> String {
+    format!("{}/{}/resolve/main/{}.bin", HF_BASE, def.repo, def.id)
+}
+
+/// Whether the model can detect speaker turns (tinydiarize).
+pub fn supports_tdrz(model_id: &str) -> bool {
+    CATALOG.iter().any(|def| def.id == model_id && def.tdrz)
+}
+
+pub fn list_models(app_data_dir: &Path) -> Vec<ModelInfo> {
     CATALOG
         .iter()
         .map(|def| ModelInfo {
@@ -74,9 +93,10 @@ pub fn list_models(app_data_dir: &PathBuf) -> Vec<ModelInfo> {
             name: def.name.to_string(),
             size_bytes: def.size_bytes,
             size_label: def.size_label.to_string(),
-            url: format!("{}/{}.bin", HF_BASE, def.id),
+            url: download_url(def),
             is_english_only: def.english_only,
             is_quantized: def.quantized,
+            supports_diarization: def.tdrz,
             is_downloaded: model_file(app_data_dir, def.id).exists(),
         })
         .collect()
@@ -87,10 +107,14 @@ pub async fn download_model(
     app_data_dir: PathBuf,
     model_id: String,
 ) -> AppResult<()> {
+    let def = CATALOG
+        .iter()
+        .find(|def| def.id == model_id)
+        .ok_or_else(|| AppError::Download(format!("Modèle inconnu : {}", model_id)))?;
     let dir = models_dir(&app_data_dir);
     std::fs::create_dir_all(&dir)?;
 
-    let url = format!("{}/{}.bin", HF_BASE, model_id);
+    let url = download_url(def);
     let part_path = dir.join(format!("{}.bin.part", model_id));
     let final_path = dir.join(format!("{}.bin", model_id));
 
@@ -141,7 +165,7 @@ pub async fn download_model(
     Ok(())
 }
 
-pub fn delete_model(app_data_dir: &PathBuf, model_id: &str) -> AppResult<()> {
+pub fn delete_model(app_data_dir: &Path, model_id: &str) -> AppResult<()> {
     let path = model_file(app_data_dir, model_id);
     if path.exists() {
         std::fs::remove_file(&path)?;
@@ -0,0 +1,843 @@
+//! Inverse text normalization: spoken numbers, ordinals, dates, times,
+//! currencies and percentages are rewritten in their written form
+//! ("twenty three percent" -> "23%", "le premier mars" -> "le 1er mars").
+//! English and French are supported; other languages pass through.
+
+use crate::config::NormalizationConfig;
+use crate::voice_commands::{split_words, Word};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lang {
+    En,
+    Fr,
+}
+
+impl Lang {
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "en" => Some(Lang::En),
+            "fr" => Some(Lang::Fr),
+            _ => None,
+        }
+    }
+}
+
+/// Position a number word takes in a compound number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+}
+
+use Kind::*;
+
+type Lexicon = &'static [(&'static str, u64, Kind)];
+
+const EN_CARDINALS: Lexicon = &[
+    ("zero", 0, Unit), ("one", 1, Unit), ("two", 2, Unit), ("three", 3, Unit),
+    ("four", 4, Unit), ("five", 5, Unit), ("six", 6, Unit), ("seven", 7, Unit),
+    ("eight", 8, Unit), ("nine", 9, Unit), ("ten", 10, Teen), ("eleven", 11, Teen),
+    ("twelve", 12, Teen), ("thirteen", 13, Teen), ("fourteen", 14, Teen),
+    ("fifteen", 15, Teen), ("sixteen", 16, Teen), ("seventeen", 17, Teen),
+    ("eighteen", 18, Teen), ("nineteen", 19, Teen), ("twenty", 20, Tens),
+    ("thirty", 30, Tens), ("forty", 40, Tens), ("fifty", 50, Tens), ("sixty", 60, Tens),
+    ("seventy", 70, Tens), ("eighty", 80, Tens), ("ninety", 90, Tens),
+    ("hundred", 100, Hundred), ("thousand", 1_000, Scale), ("million", 1_000_000, Scale),
+    ("billion", 1_000_000_000, Scale),
+];
+
+const EN_ORDINALS: Lexicon = &[
+    ("first", 1, Unit), ("second", 2, Unit), ("third", 3, Unit), ("fourth", 4, Unit),
+    ("fifth", 5, Unit), ("sixth", 6, Unit), ("seventh", 7, Unit), ("eighth", 8, Unit),
+    ("ninth", 9, Unit), ("tenth", 10, Teen), ("eleventh", 11, Teen), ("twelfth", 12, Teen),
+    ("thirteenth", 13, Teen), ("fourteenth", 14, Teen), ("fifteenth", 15, Teen),
+    ("sixteenth", 16, Teen), ("seventeenth", 17, Teen), ("eighteenth", 18, Teen),
+    ("nineteenth", 19, Teen), ("twentieth", 20, Tens), ("thirtieth", 30, Tens),
+    ("fortieth", 40, Tens), ("fiftieth", 50, Tens), ("sixtieth", 60, Tens),
+    ("seventieth", 70, Tens), ("eightieth", 80, Tens), ("ninetieth", 90, Tens),
+    ("hundredth", 100, Hundred), ("thousandth", 1_000, Scale),
+    ("millionth", 1_000_000, Scale), ("billionth", 1_000_000_000, Scale),
+];
+
+const FR_CARDINALS: Lexicon = &[
+    ("zéro", 0, Unit), ("zero", 0, Unit), ("un", 1, Unit), ("une", 1, Unit),
+    ("deux", 2, Unit), ("trois", 3, Unit), ("quatre", 4, Unit), ("cinq", 5, Unit),
+    ("six", 6, Unit), ("sept", 7, Unit), ("huit", 8, Unit), ("neuf", 9, Unit),
+    ("dix", 10, Teen), ("onze", 11, Teen), ("douze", 12, Teen), ("treize", 13, Teen),
+    ("quatorze", 14, Teen), ("quinze", 15, Teen), ("seize", 16, Teen),
+    ("vingt", 20, Tens), ("trente", 30, Tens), ("quarante", 40, Tens),
+    ("cinquante", 50, Tens), ("soixante", 60, Tens), ("septante", 70, Tens),
+    ("huitante", 80, Tens), ("octante", 80, Tens), ("nonante", 90, Tens),
+    ("quatre vingt", 80, Tens), ("quatre vingts", 80, Tens),
+    ("cent", 100, Hundred), ("cents", 100, Hundred), ("mille", 1_000, Scale),
+    ("million", 1_000_000, Scale), ("millions", 1_000_000, Scale),
+    ("milliard", 1_000_000_000, Scale), ("milliards", 1_000_000_000, Scale),
+];
+
+const FR_ORDINALS: Lexicon = &[
+    ("premier", 1, Unit), ("première", 1, Unit), ("unième", 1, Unit),
+    ("deuxième", 2, Unit), ("second", 2, Unit), ("seconde", 2, Unit),
+    ("troisième", 3, Unit), ("quatrième", 4, Unit), ("cinquième", 5, Unit),
+    ("sixième", 6, Unit), ("septième", 7, Unit), ("huitième", 8, Unit),
+    ("neuvième", 9, Unit), ("dixième", 10, Teen), ("onzième", 11, Teen),
+    ("douzième", 12, Teen), ("treizième", 13, Teen), ("quatorzième", 14, Teen),
+    ("quinzième", 15, Teen), ("seizième", 16, Teen), ("vingtième", 20, Tens),
+    ("trentième", 30, Tens), ("quarantième", 40, Tens), ("cinquantième", 50, Tens),
+    ("soixantième", 60, Tens), ("quatre vingtième", 80, Tens),
+    ("centième", 100, Hundred), ("millième", 1_000, Scale),
+    ("millionième", 1_000_000, Scale), ("milliardième", 1_000_000_000, Scale),
+];
+
+const EN_MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August",
+    "September", "October", "November", "December",
+];
+
+const FR_MONTHS: [&str; 12] = [
+    "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août",
+    "septembre", "octobre", "novembre", "décembre",
+];
+
+/// (spoken form, symbol)
+const EN_CURRENCIES: &[(&str, &str)] = &[
+    ("dollar", "$"), ("dollars", "$"), ("euro", "€"), ("euros", "€"),
+    ("pound", "£"), ("pounds", "£"),
+];
+
+const FR_CURRENCIES: &[(&str, &str)] = &[
+    ("euro", "€"), ("euros", "€"), ("dollar", "$"), ("dollars", "$"),
+    ("livre sterling", "£"), ("livres sterling", "£"),
+];
+
+/// A rewritten span of words.
+struct Match {
+    text: String,
+    len: usize,
+}
+
+/// A number read from the transcript, either spelled out or in digits.
+struct Number {
+    value: u64,
+    /// Fractional digits, if any
+    decimals: Option<String>,
+    len: usize,
+    ordinal: bool,
+    /// "première" -> 1re
+    feminine: bool,
+    spoken: bool,
+}
+
+struct Normalizer<'a> {
+    lang: Lang,
+    words: &'a [Word<'a>],
+    config: &'a NormalizationConfig,
+}
+
+impl<'a> Normalizer<'a> {
+    fn key(&self, i: usize) -> &str {
+        self.words.get(i).map(|w| w.key.as_str()).unwrap_or("")
+    }
+
+    /// Number of words `phrase` spans when it starts at `i`.
+    fn phrase_at(&self, i: usize, phrase: &str) -> Option<usize> {
+        let mut len = 0;
+        for part in phrase.split(' ') {
+            if self.key(i + len) != part {
+                return None;
+            }
+            len += 1;
+        }
+        Some(len)
+    }
+
+    fn any_phrase_at(&self, i: usize, phrases: &[&str]) -> Option<usize> {
+        phrases.iter().filter_map(|p| self.phrase_at(i, p)).max()
+    }
+
+    /// Longest number word at `i`: (value, kind, ordinal, feminine, len).
+    fn lookup(&self, i: usize) -> Option<(u64, Kind, bool, bool, usize)> {
+        let (cardinals, ordinals) = match self.lang {
+            Lang::En => (EN_CARDINALS, EN_ORDINALS),
+            Lang::Fr => (FR_CARDINALS, FR_ORDINALS),
+        };
+        let mut best: Option<(u64, Kind, bool, bool, usize)> = None;
+        for (table, ordinal) in [(cardinals, false), (ordinals, true)] {
+            for &(phrase, value, kind) in table {
+                if let Some(len) = self.phrase_at(i, phrase) {
+                    if best.map(|b| len > b.4).unwrap_or(true) {
+                        best = Some((value, kind, ordinal, phrase.ends_with("ère"), len));
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Whether `and` / `et` at `i` joins two parts of the same number.
+    fn connector_fits(&self, i: usize, group: u64, total: u64) -> bool {
+        let Some((value, kind, ..)) = self.lookup(i + 1) else {
+            return false;
+        };
+        match self.lang {
+            // "one hundred and five", "two thousand and one"
+            Lang::En => {
+                self.key(i) == "and"
+                    && kind != Hundred
+                    && kind != Scale
+                    && ((group > 0 && group.is_multiple_of(100)) || (group == 0 && total > 0))
+            }
+            // "vingt et un", "soixante et onze"
+            Lang::Fr => {
+                self.key(i) == "et"
+                    && matches!(group % 100, 20 | 30 | 40 | 50 | 60)
+                    && (value == 1 || value == 11)
+            }
+        }
+    }
+
+    /// Spelled-out number starting at `i`.
+    fn spoken_number(&self, i: usize) -> Option<Number> {
+        let mut total = 0u64;
+        let mut group = 0u64;
+        let mut last_scale = u64::MAX;
+        let mut j = i;
+        let mut end = i;
+        let mut ordinal = false;
+        let mut feminine = false;
+
+        while j < self.words.len() {
+            if end > i && self.connector_fits(j, group, total) {
+                j += 1;
+                continue;
+            }
+
+            // "a hundred", "a thousand"
+            if self.lang == Lang::En && end == i && self.key(j) == "a" {
+                match self.lookup(j + 1) {
+                    Some((_, Hundred | Scale, ..)) => {
+                        group = 1;
+                        j += 1;
+                        continue;
+                    }
+                    _ => break,
+                }
+            }
+
+            let Some((value, kind, is_ordinal, is_feminine, len)) = self.lookup(j) else {
+                break;
+            };
+            let low = group % 100;
+            let fits = match kind {
+                _ if value == 0 => end == i,
+                Unit => low.is_multiple_of(10) && (low != 10 || self.lang == Lang::Fr),
+                Teen => low == 0 || (self.lang == Lang::Fr && (low == 60 || low == 80)),
+                Tens => low == 0,
+                Hundred => group < 100,
+                Scale => value < last_scale && (group > 0 || total == 0),
+            };
+            if !fits {
+                break;
+            }
+
+            match kind {
+                Hundred => group = group.max(1) * 100,
+                Scale => {
+                    total += group.max(1) * value;
+                    group = 0;
+                    last_scale = value;
+                }
+                _ => group += value,
+            }
+            j += len;
+            end = j;
+
+            if is_ordinal {
+                ordinal = true;
+                feminine = is_feminine;
+                break;
+            }
+            if value == 0 {
+                break;
+            }
+        }
+
+        (end > i).then_some(Number {
+            value: total + group,
+            decimals: None,
+            len: end - i,
+            ordinal,
+            feminine,
+            spoken: true,
+        })
+    }
+
+    /// "point five" (digit by digit) / "virgule vingt-cinq" after a number.
+    fn decimals(&self, i: usize) -> Option<(String, usize)> {
+        let mut digits = String::new();
+        let mut j = i + 1;
+
+        match self.lang {
+            Lang::En => {
+                if self.key(i) != "point" {
+                    return None;
+                }
+                while j < self.words.len() {
+                    match self.lookup(j) {
+                        Some((value, Unit, false, _, 1)) => digits.push_str(&value.to_string()),
+                        _ if self.key(j) == "oh" => digits.push('0'),
+                        _ => break,
+                    }
+                    j += 1;
+                }
+            }
+            Lang::Fr => {
+                if self.key(i) != "virgule" {
+                    return None;
+                }
+                while matches!(self.key(j), "zéro" | "zero") {
+                    digits.push('0');
+                    j += 1;
+                }
+                if let Some(n) = self.spoken_number(j).filter(|n| !n.ordinal && n.value > 0) {
+                    digits.push_str(&n.value.to_string());
+                    j += n.len;
+                }
+            }
+        }
+
+        (!digits.is_empty()).then_some((digits, j - i))
+    }
+
+    /// A number at `i`, spelled out (with optional decimals) or in digits.
+    fn number(&self, i: usize) -> Option<Number> {
+        let key = self.key(i);
+        if !key.is_empty() && key.chars().all(|c| c.is_ascii_digit()) {
+            return Some(Number {
+                value: key.parse().ok()?,
+                decimals: None,
+                len: 1,
+                ordinal: false,
+                feminine: false,
+                spoken: false,
+            });
+        }
+
+        let mut number = self.spoken_number(i)?;
+        if !number.ordinal {
+            if let Some((digits, len)) = self.decimals(i + number.len) {
+                number.decimals = Some(digits);
+                number.len += len;
+            }
+        }
+        Some(number)
+    }
+
+    fn decimal_separator(&self) -> char {
+        match self.lang {
+            Lang::En => '.',
+            Lang::Fr => ',',
+        }
+    }
+
+    fn format_number(&self, number: &Number) -> String {
+        match &number.decimals {
+            Some(digits) => format!("{}{}{}", number.value, self.decimal_separator(), digits),
+            None => number.value.to_string(),
+        }
+    }
+
+    fn format_ordinal(&self, value: u64, feminine: bool) -> String {
+        match self.lang {
+            Lang::En => {
+                let suffix = match (value % 10, value % 100) {
+                    (_, 11..=13) => "th",
+                    (1, _) => "st",
+                    (2, _) => "nd",
+                    (3, _) => "rd",
+                    _ => "th",
+                };
+                format!("{}{}", value, suffix)
+            }
+            Lang::Fr => match (value, feminine) {
+                (1, false) => "1er".to_string(),
+                (1, true) => "1re".to_string(),
+                _ => format!("{}e", value),
+            },
+        }
+    }
+
+    fn month(&self, i: usize) -> Option<usize> {
+        let key = self.key(i);
+        let months = match self.lang {
+            Lang::En => &EN_MONTHS,
+            Lang::Fr => &FR_MONTHS,
+        };
+        let month = months.iter().position(|m| {
+            let m = m.to_lowercase();
+            // Accept unaccented spellings ("fevrier", "aout")
+            m == key || m.replace('é', "e").replace('û', "u") == key
+        })?;
+        // "you may first check" is not a date
+        let capitalized = self.words[i].text.starts_with(char::is_uppercase);
+        (self.lang != Lang::En || key != "may" || capitalized).then_some(month)
+    }
+
+    /// Day of the month at `i`: "first", "1st", "premier", "15", "quinze".
+    fn day(&self, i: usize) -> Option<(u64, usize)> {
+        let key = self.key(i);
+        let digits = key.trim_end_matches(|c: char| c.is_alphabetic());
+        let suffix = &key[digits.len()..];
+        if !digits.is_empty()
+            && digits.chars().all(|c| c.is_ascii_digit())
+            && matches!(suffix, "" | "st" | "nd" | "rd" | "th" | "er" | "re" | "e")
+        {
+            let day: u64 = digits.parse().ok()?;
+            return (1..=31).contains(&day).then_some((day, 1));
+        }
+
+        let n = self.spoken_number(i)?;
+        // French days are cardinal except "premier"
+        let valid = match self.lang {
+            Lang::En => true,
+            Lang::Fr => !n.ordinal || n.value == 1,
+        };
+        (valid && (1..=31).contains(&n.value)).then_some((n.value, n.len))
+    }
+
+    /// Year at `i`: "2024", "two thousand twenty four", "nineteen ninety nine".
+    fn year(&self, i: usize) -> Option<(u64, usize)> {
+        let n = self.number(i).filter(|n| !n.ordinal && n.decimals.is_none())?;
+        if (1000..=2999).contains(&n.value) {
+            return Some((n.value, n.len));
+        }
+
+        // English pairs: "twenty twenty four", "nineteen oh five"
+        if self.lang == Lang::En && n.spoken && (10..=29).contains(&n.value) {
+            let j = i + n.len;
+            if self.key(j) == "oh" {
+                if let Some((digit, Unit, false, _, 1)) = self.lookup(j + 1) {
+                    return Some((n.value * 100 + digit, n.len + 2));
+                }
+            }
+            let m = self.spoken_number(j).filter(|m| !m.ordinal && (10..=99).contains(&m.value))?;
+            return Some((n.value * 100 + m.value, n.len + m.len));
+        }
+        None
+    }
+
+    fn date(&self, i: usize) -> Option<Match> {
+        match self.lang {
+            Lang::En => {
+                // "the first of January [2024]"
+                let (day, month, len) = if self.key(i) == "the" {
+                    let n = self.spoken_number(i + 1).filter(|n| n.ordinal)?;
+                    let of = i + 1 + n.len;
+                    if self.key(of) != "of" || !(1..=31).contains(&n.value) {
+                        return None;
+                    }
+                    (n.value, self.month(of + 1)?, n.len + 3)
+                } else {
+                    // "January first [2024]"
+                    let month = self.month(i)?;
+                    let (day, len) = self.day(i + 1)?;
+                    (day, month, len + 1)
+                };
+
+                let mut text = format!("{} {}", EN_MONTHS[month], day);
+                let mut total = len;
+                if let Some((year, year_len)) = self.year(i + len) {
+                    text.push_str(&format!(", {}", year));
+                    total += year_len;
+                }
+                Some(Match { text, len: total })
+            }
+            Lang::Fr => {
+                // "premier janvier [deux mille vingt-quatre]"
+                let (day, day_len) = self.day(i)?;
+                let month = self.month(i + day_len)?;
+                let mut text = format!("{} {}", self.format_day_fr(day), FR_MONTHS[month]);
+                let mut len = day_len + 1;
+                if let Some((year, year_len)) = self.year(i + len) {
+                    text.push_str(&format!(" {}", year));
+                    len += year_len;
+                }
+                Some(Match { text, len })
+            }
+        }
+    }
+
+    fn format_day_fr(&self, day: u64) -> String {
+        if day == 1 {
+            "1er".to_string()
+        } else {
+            day.to_string()
+        }
+    }
+
+    /// "a.m."/"pm" and similar at `i`.
+    fn meridiem(&self, i: usize) -> Option<&'static str> {
+        match self.key(i) {
+            "am" | "a.m" => Some("AM"),
+            "pm" | "p.m" => Some("PM"),
+            _ => None,
+        }
+    }
+
+    fn time(&self, i: usize) -> Option<Match> {
+        let hour = self.number(i).filter(|n| !n.ordinal && n.decimals.is_none())?;
+        let j = i + hour.len;
+
+        match self.lang {
+            Lang::En => {
+                if !(1..=12).contains(&hour.value) {
+                    return None;
+                }
+                if self.key(j) == "o'clock" {
+                    return Some(Match {
+                        text: format!("{}:00", hour.value),
+                        len: hour.len + 1,
+                    });
+                }
+                if let Some(meridiem) = self.meridiem(j) {
+                    return Some(Match {
+                        text: format!("{} {}", hour.value, meridiem),
+                        len: hour.len + 1,
+                    });
+                }
+
+                // "three thirty pm", "seven oh five am"
+                let (minutes, minutes_len) = if self.key(j) == "oh" {
+                    match self.lookup(j + 1) {
+                        Some((digit, Unit, false, _, 1)) if digit > 0 => (digit, 2),
+                        _ => return None,
+                    }
+                } else {
+                    let m = self.number(j).filter(|m| !m.ordinal && m.decimals.is_none())?;
+                    (m.value, m.len)
+                };
+                if minutes > 59 {
+                    return None;
+                }
+                let meridiem = self.meridiem(j + minutes_len)?;
+                Some(Match {
+                    text: format!("{}:{:02} {}", hour.value, minutes, meridiem),
+                    len: hour.len + minutes_len + 1,
+                })
+            }
+            Lang::Fr => {
+                if hour.value > 23 || !matches!(self.key(j), "heure" | "heures") {
+                    return None;
+                }
+                let k = j + 1;
+                // "deux heures trente minutes" is a duration, not a time
+                if let Some(m) = self.number(k) {
+                    if matches!(self.key(k + m.len), "minute" | "minutes") {
+                        return None;
+                    }
+                }
+                let minutes = if let Some(len) = self.any_phrase_at(k, &["et quart"]) {
+                    Some((15, len))
+                } else if let Some(len) = self.any_phrase_at(k, &["et demie", "et demi"]) {
+                    Some((30, len))
+                } else {
+                    self.number(k)
+                        .filter(|m| !m.ordinal && m.decimals.is_none() && (1..=59).contains(&m.value))
+                        .map(|m| (m.value, m.len))
+                };
+
+                Some(match minutes {
+                    Some((minutes, len)) => Match {
+                        text: format!("{} h {:02}", hour.value, minutes),
+                        len: hour.len + 1 + len,
+                    },
+                    None => Match {
+                        text: format!("{} h", hour.value),
+                        len: hour.len + 1,
+                    },
+                })
+            }
+        }
+    }
+
+    fn percent(&self, number: &Number, j: usize) -> Option<Match> {
+        let phrases: &[&str] = match self.lang {
+            Lang::En => &["percent", "per cent"],
+            Lang::Fr => &["pour cent", "pourcent"],
+        };
+        let len = self.any_phrase_at(j, phrases)?;
+        let value = self.format_number(number);
+        Some(Match {
+            text: match self.lang {
+                Lang::En => format!("{}%", value),
+                Lang::Fr => format!("{} %", value),
+            },
+            len: number.len + len,
+        })
+    }
+
+    fn currency(&self, number: &Number, j: usize) -> Option<Match> {
+        let currencies = match self.lang {
+            Lang::En => EN_CURRENCIES,
+            Lang::Fr => FR_CURRENCIES,
+        };
+        let (symbol, len) = currencies
+            .iter()
+            .filter_map(|(phrase, symbol)| self.phrase_at(j, phrase).map(|len| (*symbol, len)))
+            .max_by_key(|(_, len)| *len)?;
+        let mut total = number.len + len;
+
+        // "five dollars and twenty cents", "cinq euros cinquante"
+        let mut cents = None;
+        if number.decimals.is_none() {
+            let mut k = j + len;
+            let joined = matches!(self.key(k), "and" | "et");
+            if joined {
+                k += 1;
+            }
+            if let Some(c) = self.number(k).filter(|c| !c.ordinal && c.decimals.is_none() && c.value < 100) {
+                let unit = self.any_phrase_at(k + c.len, &["cent", "cents", "centime", "centimes"]);
+                // "trois euros et deux baguettes" is not an amount in cents
+                let unit_required = joined || self.lang == Lang::En;
+                if unit.is_some() || !unit_required {
+                    cents = Some(c.value);
+                    total = k + c.len + unit.unwrap_or(0) - (j - number.len);
+                }
+            }
+        }
+
+        let amount = match cents {
+            Some(c) => format!("{}{}{:02}", number.value, self.decimal_separator(), c),
+            None => self.format_number(number),
+        };
+        Some(Match {
+            text: match self.lang {
+                Lang::En => format!("{}{}", symbol, amount),
+                Lang::Fr => format!("{} {}", amount, symbol),
+            },
+            len: total,
+        })
+    }
+
+    fn match_at(&self, i: usize) -> Option<Match> {
+        if self.config.dates {
+            if let Some(m) = self.date(i) {
+                return Some(m);
+            }
+        }
+        if self.config.times {
+            if let Some(m) = self.time(i) {
+                return Some(m);
+            }
+        }
+
+        let number = self.number(i)?;
+        let j = i + number.len;
+        if !number.ordinal {
+            if self.config.percentages {
+                if let Some(m) = self.percent(&number, j) {
+                    return Some(m);
+                }
+            }
+            if self.config.currencies {
+                if let Some(m) = self.currency(&number, j) {
+                    return Some(m);
+                }
+            }
+        }
+
+        // Digits are already written; lone small numbers read better as words
+        if !number.spoken || (number.len == 1 && number.value < 10) {
+            return None;
+        }
+        if number.ordinal {
+            self.config.ordinals.then(|| Match {
+                text: self.format_ordinal(number.value, number.feminine),
+                len: number.len,
+            })
+        } else {
+            self.config.numbers.then(|| Match {
+                text: self.format_number(&number),
+                len: number.len,
+            })
+        }
+    }
+}
+
+fn leading_punctuation(text: &str) -> &str {
+    let start = text.find(|c: char| c.is_alphanumeric()).unwrap_or(text.len());
+    &text[..start]
+}
+
+fn trailing_punctuation(text: &str) -> &str {
+    let end = text
+        .rfind(|c: char| c.is_alphanumeric())
+        .map(|i| i + text[i..].chars().next().map(char::len_utf8).unwrap_or(1))
+        .unwrap_or(0);
+    &text[end..]
+}
+
+/// Rewrite spoken numbers, dates, times, amounts and percentages in `text`.
+pub fn normalize(text: &str, language: &str, config: &NormalizationConfig) -> String {
+    let Some(lang) = Lang::from_code(language) else {
+        return text.to_string();
+    };
+    if !config.enabled {
+        return text.to_string();
+    }
+
+    let words = split_words(text);
+    let normalizer = Normalizer {
+        lang,
+        words: &words,
+        config,
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < words.len() {
+        let word = &words[i];
+        if !out.is_empty() {
+            out.push_str(word.separator);
+        }
+
+        match normalizer.match_at(i) {
+            Some(m) => {
+                let last = &words[i + m.len - 1];
+                let mut tail = trailing_punctuation(last.text);
+                // The dot of "a.m." is not a sentence end unless the text stops there
+                if normalizer.meridiem(i + m.len - 1).is_some() && i + m.len < words.len() {
+                    tail = tail.strip_prefix('.').unwrap_or(tail);
+                }
+                out.push_str(leading_punctuation(word.text));
+                out.push_str(&m.text);
+                out.push_str(tail);
+                i += m.len;
+            }
+            None => {
+                out.push_str(word.text);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn en(text: &str) -> String {
+        normalize(text, "en", &NormalizationConfig::default())
+    }
+
+    fn fr(text: &str) -> String {
+        normalize(text, "fr", &NormalizationConfig::default())
+    }
+
+    #[test]
+    fn english_cardinals() {
+        assert_eq!(en("twenty three people"), "23 people");
+        assert_eq!(en("one hundred and five"), "105");
+        assert_eq!(en("a thousand words"), "1000 words");
+        assert_eq!(en("two million three hundred thousand"), "2300000");
+        assert_eq!(en("three point one four"), "3.14");
+    }
+
+    #[test]
+    fn french_cardinals() {
+        assert_eq!(fr("quatre-vingt-dix-sept ans"), "97 ans");
+        assert_eq!(fr("soixante et onze"), "71");
+        assert_eq!(fr("vingt et un"), "21");
+        assert_eq!(fr("quatre-vingts"), "80");
+        assert_eq!(fr("deux mille trois cents"), "2300");
+        assert_eq!(fr("trois virgule cinq"), "3,5");
+    }
+
+    #[test]
+    fn ordinals() {
+        assert_eq!(en("the twenty first century"), "the 21st century");
+        assert_eq!(en("my eleventh try"), "my 11th try");
+        assert_eq!(fr("la trentième fois"), "la 30e fois");
+        assert_eq!(fr("le vingt et unième siècle"), "le 21e siècle");
+    }
+
+    #[test]
+    fn lone_small_numbers_stay_words() {
+        assert_eq!(en("I have three cats"), "I have three cats");
+        assert_eq!(en("the second try"), "the second try");
+        assert_eq!(fr("la première fois"), "la première fois");
+    }
+
+    #[test]
+    fn percentages() {
+        assert_eq!(en("fifty percent off"), "50% off");
+        assert_eq!(en("twelve point five per cent"), "12.5%");
+        assert_eq!(fr("vingt pour cent"), "20 %");
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(en("at three thirty pm today"), "at 3:30 PM today");
+        assert_eq!(en("seven oh five a.m."), "7:05 AM.");
+        assert_eq!(en("five o'clock"), "5:00");
+        assert_eq!(fr("à quatorze heures trente"), "à 14 h 30");
+        assert_eq!(fr("huit heures et quart"), "8 h 15");
+    }
+
+    #[test]
+    fn durations_are_not_times() {
+        assert_eq!(fr("deux heures trente minutes"), "deux heures 30 minutes");
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(en("March third twenty twenty four"), "March 3, 2024");
+        assert_eq!(en("the first of January"), "January 1");
+        assert_eq!(fr("le premier mars"), "le 1er mars");
+        assert_eq!(fr("le quinze août deux mille vingt-quatre"), "le 15 août 2024");
+    }
+
+    #[test]
+    fn ordinary_words_are_left_alone() {
+        assert_eq!(en("one of them"), "one of them");
+        assert_eq!(en("you may first check"), "you may first check");
+        assert_eq!(en("at second glance"), "at second glance");
+        assert_eq!(fr("il y a un chat"), "il y a un chat");
+        assert_eq!(en("a lot of people"), "a lot of people");
+    }
+
+    #[test]
+    fn disabled_categories_pass_through() {
+        let config = NormalizationConfig {
+            numbers: false,
+            ..NormalizationConfig::default()
+        };
+        assert_eq!(
+            normalize("twenty three percent of twenty three", "en", &config),
+            "23% of twenty three"
+        );
+        assert_eq!(normalize("vingt-trois", "de", &NormalizationConfig::default()), "vingt-trois");
+    }
+
+    #[test]
+    fn cents_after_et_need_their_unit() {
+        assert_eq!(fr("trois euros et deux baguettes"), "3 € et deux baguettes");
+        assert_eq!(fr("trois euros et vingt centimes"), "3,20 €");
+    }
+
+    #[test]
+    fn cents_may_follow_the_currency_directly() {
+        assert_eq!(fr("cinq euros cinquante"), "5,50 €");
+    }
+
+    #[test]
+    fn english_cents_need_their_unit() {
+        let config = NormalizationConfig::default();
+        assert_eq!(normalize("five dollars and twenty cents", "en", &config), "$5.20");
+        assert_eq!(normalize("five dollars and two apples", "en", &config), "$5 and two apples");
+    }
+}
@@ -1,7 +1,7 @@
-/// macOS permission checks for microphone and accessibility.
-/// Uses native APIs (AVCaptureDevice, AXIsProcessTrusted) called from
-/// the app's own process so the checks reflect LocalWhisper's permissions.
-/// On non-macOS platforms, permissions are always reported as granted.
+//! macOS permission checks for microphone and accessibility.
+//! Uses native APIs (AVCaptureDevice, AXIsProcessTrusted) called from
+//! the app's own process so the checks reflect LocalWhisper's permissions.
+//! On non-macOS platforms, permissions are always reported as granted.
 
 use serde::Serialize;
 
@@ -0,0 +1,253 @@
+//! User-defined text transforms applied to a dictation before it reaches the
+//! clipboard. Rules run in order; each one can be switched off individually.
+
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::errors::{AppError, AppResult};
+
+const RULES_FILE: &str = "text_rules.json";
+/// Rules listed under this key run for every language, before the
+/// language-specific ones.
+const ANY_LANGUAGE: &str = "*";
+
+/// Ordered rule lists keyed by language code.
+pub type TextRuleSets = HashMap<String, Vec<TextRule>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextRule {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub transform: Transform,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transform {
+    /// Find/replace; `replacement` may reference groups as `$1` or `${name}`
+    Regex {
+        pattern: String,
+        replacement: String,
+        #[serde(default)]
+        case_insensitive: bool,
+    },
+    CapitalizeFirst,
+    RemoveTrailingPeriod,
+    /// Collapse runs of spaces into one and trim each line; line breaks are kept
+    NormalizeWhitespace,
+    Case { case: TextCase },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextCase {
+    Lower,
+    Upper,
+    /// First letter of every word
+    Title,
+    /// First letter of every sentence, the rest lowercase
+    Sentence,
+}
+
+fn default_rules() -> TextRuleSets {
+    let rules = vec![
+        TextRule {
+            enabled: true,
+            transform: Transform::NormalizeWhitespace,
+        },
+        TextRule {
+            enabled: false,
+            transform: Transform::CapitalizeFirst,
+        },
+        TextRule {
+            enabled: false,
+            transform: Transform::RemoveTrailingPeriod,
+        },
+    ];
+    HashMap::from([(ANY_LANGUAGE.to_string(), rules)])
+}
+
+/// Load the rule sets from `app_data_dir`, creating the file with the
+/// defaults on first use.
+pub fn load_rules(app_data_dir: &Path) -> AppResult<TextRuleSets> {
+    let path = app_data_dir.join(RULES_FILE);
+
+    if !path.exists() {
+        let rules = default_rules();
+        save_rules(app_data_dir, &rules)?;
+        return Ok(rules);
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| AppError::Config(format!("Lecture impossible : {}", e)))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Config(format!("Parsing impossible : {}", e)))
+}
+
+pub fn save_rules(app_data_dir: &Path, rules: &TextRuleSets) -> AppResult<()> {
+    for (language, list) in rules {
+        validate(list).map_err(|e| AppError::Config(format!("[{}] {}", language, e)))?;
+    }
+
+    std::fs::create_dir_all(app_data_dir)?;
+    let json = serde_json::to_string_pretty(rules)
+        .map_err(|e| AppError::Config(format!("Sérialisation impossible : {}", e)))?;
+    std::fs::write(app_data_dir.join(RULES_FILE), json)?;
+    Ok(())
+}
+
+/// Reject rule lists containing an invalid regular expression.
+pub fn validate(rules: &[TextRule]) -> AppResult<()> {
+    for (i, rule) in rules.iter().enumerate() {
+        if let Transform::Regex {
+            pattern,
+            case_insensitive,
+            ..
+        } = &rule.transform
+        {
+            RegexBuilder::new(pattern)
+                .case_insensitive(*case_insensitive)
+                .build()
+                .map_err(|e| {
+                    AppError::Config(format!("Règle {} : expression invalide : {}", i + 1, e))
+                })?;
+        }
+    }
+    Ok(())
+}
+
+pub fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn title_case(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for c in text.chars() {
+        if at_word_start && c.is_alphabetic() {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        at_word_start = c.is_whitespace() || c == '-';
+    }
+    out
+}
+
+fn sentence_case(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut at_sentence_start = true;
+    for c in text.chars() {
+        if at_sentence_start && c.is_alphabetic() {
+            out.extend(c.to_uppercase());
+            at_sentence_start = false;
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        if matches!(c, '.' | '!' | '?' | '\n') {
+            at_sentence_start = true;
+        }
+    }
+    out
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Drop a single final period, keeping ellipses ("...") intact.
+fn remove_trailing_period(text: &str) -> String {
+    let trimmed = text.trim_end();
+    if trimmed.ends_with('.') && !trimmed.ends_with("..") {
+        trimmed[..trimmed.len() - 1].to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn apply_rule(text: String, rule: &TextRule) -> AppResult<String> {
+    Ok(match &rule.transform {
+        Transform::Regex {
+            pattern,
+            replacement,
+            case_insensitive,
+        } => {
+            let re = RegexBuilder::new(pattern)
+                .case_insensitive(*case_insensitive)
+                .build()
+                .map_err(|e| AppError::Config(format!("Expression invalide : {}", e)))?;
+            re.replace_all(&text, replacement.as_str()).into_owned()
+        }
+        Transform::CapitalizeFirst => capitalize_first(&text),
+        Transform::RemoveTrailingPeriod => remove_trailing_period(&text),
+        Transform::NormalizeWhitespace => normalize_whitespace(&text),
+        Transform::Case { case } => match case {
+            TextCase::Lower => text.to_lowercase(),
+            TextCase::Upper => text.to_uppercase(),
+            TextCase::Title => title_case(&text),
+            TextCase::Sentence => sentence_case(&text),
+        },
+    })
+}
+
+/// Run an ordered rule list over `text`. Used as-is to test a rule set
+/// against sample text.
+pub fn run_rules(text: &str, rules: &[TextRule]) -> AppResult<String> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .try_fold(text.to_string(), apply_rule)
+}
+
+/// Apply the shared rules then the ones for `language`, or only the
+/// `rule_set` list when a profile names one. A broken rule is logged and
+/// skipped so the dictation still goes through.
+pub fn process(
+    text: &str,
+    language: &str,
+    rule_set: Option<&str>,
+    rules: &TextRuleSets,
+) -> String {
+    let lists = match rule_set {
+        Some(name) => [rules.get(name), None],
+        None => [rules.get(ANY_LANGUAGE), rules.get(language)],
+    };
+    let mut out = text.to_string();
+
+    for rule in lists.into_iter().flatten().flatten().filter(|r| r.enabled) {
+        match apply_rule(out.clone(), rule) {
+            Ok(next) => out = next,
+            Err(e) => log::error!("Post-traitement : règle ignorée : {}", e),
+        }
+    }
+
+    out
+}
+
+/// Load the rule sets from `app_data_dir` and process `text`.
+pub fn apply(text: &str, language: &str, rule_set: Option<&str>, app_data_dir: &Path) -> String {
+    match load_rules(app_data_dir) {
+        Ok(rules) => process(text, language, rule_set, &rules),
+        Err(e) => {
+            log::error!("Règles de post-traitement illisibles : {}", e);
+            text.to_string()
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Per-application profiles: the first enabled profile listing the focused
+//! window's class overrides the global settings for that dictation.
+
+use crate::active_window::ActiveWindow;
+use crate::config::{AppConfig, AppProfile};
+
+pub fn find<'a>(profiles: &'a [AppProfile], window: &ActiveWindow) -> Option<&'a AppProfile> {
+    profiles
+        .iter()
+        .filter(|p| p.enabled)
+        .find(|p| p.window_classes.iter().any(|class| window.matches(class)))
+}
+
+/// Copy of `config` with the overrides of `profile` applied.
+pub fn apply(config: &AppConfig, profile: &AppProfile) -> AppConfig {
+    let mut config = config.clone();
+    if let Some(language) = &profile.language {
+        config.language = language.clone();
+    }
+    if let Some(model) = &profile.model {
+        config.active_model = Some(model.clone());
+    }
+    if let Some(voice_commands) = profile.voice_commands {
+        config.voice_commands = voice_commands;
+    }
+    if let Some(auto_paste) = profile.auto_paste {
+        config.auto_paste = auto_paste;
+    }
+    if let Some(chord) = &profile.paste_chord {
+        config.paste_chord = chord.clone();
+    }
+    if let Some(mode) = profile.output_mode {
+        config.output_mode = mode;
+    }
+    if let Some(names) = &profile.sinks {
+        for sink in &mut config.sinks {
+            sink.enabled = names.contains(&sink.name);
+        }
+    }
+    config
+}
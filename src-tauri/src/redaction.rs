@@ -0,0 +1,254 @@
+//! Detection of personal data (emails, phone numbers, IBANs, card numbers and
+//! user patterns) in a dictation or a file transcript, so it can be masked
+//! before the text reaches the clipboard or the transcript file.
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{RedactionAction, RedactionConfig};
+use crate::errors::{AppError, AppResult};
+
+const EMAIL: &str = r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b";
+const IBAN: &str = r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b";
+const CARD: &str = r"\b\d(?:[ -]?\d){12,18}\b";
+const PHONE: &str = r"(?:\+|\(|\b)\d(?:[ .\-]?\(?\d+\)?)+\b";
+
+/// Emitted as `redaction-warning` for matches configured with `warn`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionWarning {
+    /// email, phone, iban, credit_card, or the name of a custom pattern
+    pub kind: String,
+    pub value: String,
+}
+
+/// Extra check run on each candidate (checksums, digit counts).
+type Check = fn(&str) -> bool;
+
+struct Detector {
+    kind: String,
+    regex: Regex,
+    action: RedactionAction,
+    placeholder: String,
+    check: Check,
+}
+
+struct Found {
+    start: usize,
+    end: usize,
+    detector: usize,
+}
+
+fn any(_: &str) -> bool {
+    true
+}
+
+fn digits(text: &str) -> Vec<u32> {
+    text.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+fn luhn(text: &str) -> bool {
+    let digits = digits(text);
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (0, _) => d,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// ISO 13616 check: the rearranged number modulo 97 must be 1.
+fn iban_checksum(text: &str) -> bool {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&compact.len()) {
+        return false;
+    }
+    let (head, tail) = compact.split_at(4);
+    let mut remainder = 0u32;
+    for c in tail.chars().chain(head.chars()) {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+/// Lengths of the digit groups of `text`: "06 12-34" -> [2, 2, 2].
+fn digit_groups(text: &str) -> Vec<usize> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|group| !group.is_empty())
+        .map(str::len)
+        .collect()
+}
+
+/// Year, month and day in either order: "2024-01-15", "15.03.2024".
+fn date_shape(groups: &[usize]) -> bool {
+    matches!(groups, [4, 1..=2, 1..=2] | [1..=2, 1..=2, 2 | 4])
+}
+
+/// 8 to 15 digits shaped like a phone number: international ("+33 6 12 34
+/// 56 78"), national with its leading zero ("06 12 34 56 78", "020 7946
+/// 0958") or North American ("(555) 123-4567"). Dates and grouped amounts
+/// such as "12 500 000" have none of these shapes.
+fn phone_number(text: &str) -> bool {
+    let groups = digit_groups(text);
+    let count: usize = groups.iter().sum();
+    if !(8..=15).contains(&count) || date_shape(&groups) {
+        return false;
+    }
+    let north_american = matches!(groups.as_slice(), [3, 3, 4] | [1, 3, 3, 4]);
+    text.trim_start_matches('(').starts_with(['+', '0']) || north_american
+}
+
+fn build_detectors(config: &RedactionConfig) -> AppResult<Vec<Detector>> {
+    let mut detectors = Vec::new();
+
+    // Custom patterns first: they are the most specific
+    for custom in &config.custom {
+        let regex = RegexBuilder::new(&custom.pattern)
+            .case_insensitive(custom.case_insensitive)
+            .build()
+            .map_err(|e| {
+                AppError::Config(format!("Motif \"{}\" invalide : {}", custom.name, e))
+            })?;
+        detectors.push(Detector {
+            kind: custom.name.clone(),
+            regex,
+            action: custom.action,
+            placeholder: format!("[{}]", custom.name.to_uppercase()),
+            check: any,
+        });
+    }
+
+    // IBANs and card numbers before phones, which would match their digits
+    let builtin: [(&str, &str, RedactionAction, &str, Check); 4] = [
+        ("email", EMAIL, config.emails, "[EMAIL]", any),
+        ("iban", IBAN, config.ibans, "[IBAN]", iban_checksum),
+        ("credit_card", CARD, config.credit_cards, "[CARD]", luhn),
+        ("phone", PHONE, config.phones, "[PHONE]", phone_number),
+    ];
+    for (kind, pattern, action, placeholder, check) in builtin {
+        detectors.push(Detector {
+            kind: kind.to_string(),
+            regex: Regex::new(pattern).expect("motif integre invalide"),
+            action,
+            placeholder: placeholder.to_string(),
+            check,
+        });
+    }
+
+    detectors.retain(|d| d.action != RedactionAction::Off);
+    Ok(detectors)
+}
+
+/// Reject a configuration with an invalid custom pattern.
+pub fn validate(config: &RedactionConfig) -> AppResult<()> {
+    build_detectors(config).map(|_| ())
+}
+
+/// Replace letters and digits with `*`, keeping separators.
+fn mask(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_alphanumeric() { '*' } else { c })
+        .collect()
+}
+
+/// Redact `text` according to `config`, reporting `warn` matches through
+/// `warn`. Overlapping matches go to the detector listed first.
+pub fn redact(text: &str, config: &RedactionConfig, warn: &dyn Fn(RedactionWarning)) -> String {
+    if !config.enabled {
+        return text.to_string();
+    }
+    let detectors = match build_detectors(config) {
+        Ok(detectors) => detectors,
+        Err(e) => {
+            log::error!("Masquage : configuration ignorée : {}", e);
+            return text.to_string();
+        }
+    };
+
+    let mut found: Vec<Found> = Vec::new();
+    for (index, detector) in detectors.iter().enumerate() {
+        for m in detector.regex.find_iter(text) {
+            let overlaps = found.iter().any(|f| m.start() < f.end && f.start < m.end());
+            if m.is_empty() || overlaps || !(detector.check)(m.as_str()) {
+                continue;
+            }
+            found.push(Found {
+                start: m.start(),
+                end: m.end(),
+                detector: index,
+            });
+        }
+    }
+    found.sort_by_key(|f| f.start);
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for f in found {
+        let detector = &detectors[f.detector];
+        let value = &text[f.start..f.end];
+        out.push_str(&text[last..f.start]);
+        match detector.action {
+            RedactionAction::Mask => out.push_str(&mask(value)),
+            RedactionAction::Placeholder => out.push_str(&detector.placeholder),
+            RedactionAction::Warn | RedactionAction::Off => {
+                out.push_str(value);
+                warn(RedactionWarning {
+                    kind: detector.kind.clone(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        last = f.end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RedactionConfig {
+        RedactionConfig {
+            enabled: true,
+            emails: RedactionAction::Placeholder,
+            phones: RedactionAction::Placeholder,
+            ibans: RedactionAction::Placeholder,
+            credit_cards: RedactionAction::Placeholder,
+            custom: Vec::new(),
+        }
+    }
+
+    fn run(text: &str) -> String {
+        redact(text, &config(), &|_| {})
+    }
+
+    #[test]
+    fn phone_numbers_are_redacted() {
+        assert_eq!(run("Appelle le 06 12 34 56 78 demain"), "Appelle le [PHONE] demain");
+        assert_eq!(run("Call +44 20 7946 0958 now"), "Call [PHONE] now");
+        assert_eq!(run("Call (555) 123-4567 now"), "Call [PHONE] now");
+    }
+
+    #[test]
+    fn dates_and_amounts_are_kept() {
+        for text in ["le 2024-01-15", "le 15.03.2024", "le 01.02.2024", "soit 12 500 000 euros"] {
+            assert_eq!(run(text), text);
+        }
+    }
+}
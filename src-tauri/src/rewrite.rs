@@ -0,0 +1,205 @@
+//! Optional rewrite of a dictation by a local LLM server (llama.cpp, Ollama...)
+//! through the OpenAI-compatible chat-completions API.
+
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::config::RewriteConfig;
+use crate::errors::{AppError, AppResult};
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 2],
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+pub fn is_loopback(host: &str) -> bool {
+    if host.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    host.trim_matches(['[', ']'])
+        .parse::<IpAddr>()
+        .map(|ip| ip.is_loopback())
+        .unwrap_or(false)
+}
+
+/// HTTP client that only talks to the host it is given: redirects are not
+/// followed, so a local server cannot forward the text elsewhere after the
+/// loopback check, and system proxies are ignored. Shared with the webhook
+/// sinks.
+pub fn direct_client(timeout: Duration) -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .connect_timeout(timeout.min(Duration::from_secs(5)))
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .build()
+}
+
+/// Reject malformed endpoints, and remote ones unless `allow_remote` is set,
+/// so dictations stay on this machine by default.
+pub fn validate(config: &RewriteConfig) -> AppResult<()> {
+    let url = reqwest::Url::parse(&config.endpoint)
+        .map_err(|e| AppError::Rewrite(format!("URL invalide : {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::Rewrite(format!(
+            "Schéma non supporté : {}",
+            url.scheme()
+        )));
+    }
+    let host = url.host_str().unwrap_or("");
+    if !config.allow_remote && !is_loopback(host) {
+        return Err(AppError::Rewrite(format!(
+            "Hôte distant refusé : {} (autorisez les serveurs distants pour l'utiliser)",
+            host
+        )));
+    }
+    if config.timeout_secs == 0 {
+        return Err(AppError::Rewrite("Délai d'attente nul".to_string()));
+    }
+    Ok(())
+}
+
+/// Send `text` to the configured endpoint and return the model's answer.
+pub async fn rewrite(text: &str, config: &RewriteConfig) -> AppResult<String> {
+    validate(config)?;
+
+    let client = direct_client(Duration::from_secs(config.timeout_secs))
+        .map_err(|e| AppError::Rewrite(e.to_string()))?;
+
+    let request = ChatRequest {
+        model: &config.model,
+        messages: [
+            ChatMessage {
+                role: "system",
+                content: &config.system_prompt,
+            },
+            ChatMessage {
+                role: "user",
+                content: text,
+            },
+        ],
+        stream: false,
+    };
+
+    let response = client
+        .post(&config.endpoint)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| AppError::Rewrite(format!("Requête impossible : {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Rewrite(format!(
+            "Réponse invalide (HTTP {})",
+            response.status()
+        )));
+    }
+
+    let body: ChatResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::Rewrite(format!("Réponse illisible : {}", e)))?;
+
+    let content = body
+        .choices
+        .into_iter()
+        .next()
+        .map(|c| c.message.content.trim().to_string())
+        .unwrap_or_default();
+    if content.is_empty() {
+        return Err(AppError::Rewrite("Réponse vide".to_string()));
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(url: &str) -> RewriteConfig {
+        RewriteConfig {
+            endpoint: url.to_string(),
+            ..RewriteConfig::default()
+        }
+    }
+
+    #[test]
+    fn loopback_hosts() {
+        for host in ["127.0.0.1", "127.1.2.3", "::1", "[::1]", "localhost", "LocalHost"] {
+            assert!(is_loopback(host), "{} is loopback", host);
+        }
+        for host in ["localhost.evil.com", "0.0.0.0", "192.168.1.10", "10.0.0.1", "evil.com", ""] {
+            assert!(!is_loopback(host), "{} is not loopback", host);
+        }
+    }
+
+    #[test]
+    fn local_endpoints_are_accepted() {
+        for url in [
+            "http://127.0.0.1:11434/v1/chat/completions",
+            "http://127.1.2.3/v1/chat/completions",
+            "http://[::1]:8080/v1/chat/completions",
+            "https://localhost/v1/chat/completions",
+        ] {
+            assert!(validate(&endpoint(url)).is_ok(), "{} is accepted", url);
+        }
+    }
+
+    #[test]
+    fn remote_endpoints_are_rejected() {
+        for url in [
+            "http://localhost.evil.com/v1/chat/completions",
+            "http://0.0.0.0:11434/v1/chat/completions",
+            "http://192.168.1.20:11434/v1/chat/completions",
+            "http://127.0.0.1@evil.com/v1/chat/completions",
+            "http://evil.com/?host=127.0.0.1",
+        ] {
+            assert!(validate(&endpoint(url)).is_err(), "{} is rejected", url);
+        }
+    }
+
+    #[test]
+    fn remote_endpoints_need_the_opt_in() {
+        let config = RewriteConfig {
+            allow_remote: true,
+            ..endpoint("https://llm.example.com/v1/chat/completions")
+        };
+        assert!(validate(&config).is_ok());
+    }
+
+    #[test]
+    fn other_schemes_and_zero_timeout_are_rejected() {
+        for url in ["ftp://127.0.0.1/", "file:///etc/passwd", "ws://localhost/", "127.0.0.1:11434"] {
+            assert!(validate(&endpoint(url)).is_err(), "{} is rejected", url);
+        }
+        let config = RewriteConfig {
+            timeout_secs: 0,
+            ..RewriteConfig::default()
+        };
+        assert!(validate(&config).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Long-form audio handling: Whisper works on 30 s windows and tends to loop
+//! or drop text past that, so longer inputs are cut at silences into
+//! overlapping windows, transcribed one by one, then stitched back together.
+
+use crate::transcription::{Segment, TranscriptionResult};
+
+const SAMPLE_RATE: usize = 16000;
+/// Inputs up to this length are transcribed in one pass.
+const SINGLE_PASS_MS: usize = 30_000;
+/// A window is cut somewhere between these two lengths, at the quietest spot.
+const MIN_WINDOW_MS: usize = 20_000;
+const MAX_WINDOW_MS: usize = 28_000;
+/// Audio shared by two consecutive windows so no word is cut at a seam.
+const OVERLAP_MS: usize = 1_000;
+/// Energy analysis frame.
+const FRAME_MS: usize = 20;
+/// Runs of repeated words at a seam shorter than this are kept: a single
+/// shared word is as likely to be a genuine repetition as an overlap artifact.
+const MIN_SEAM_WORDS: usize = 2;
+/// Longest run of words compared when removing duplicated text at a seam.
+const MAX_SEAM_WORDS: usize = 24;
+
+fn ms_to_samples(ms: usize) -> usize {
+    ms * SAMPLE_RATE / 1000
+}
+
+/// A slice of the input, in samples.
+#[derive(Debug, Clone, Copy)]
+pub struct Window {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Window {
+    pub fn offset_ms(&self) -> i64 {
+        (self.start * 1000 / SAMPLE_RATE) as i64
+    }
+}
+
+fn frame_rms(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f32 = frame.iter().map(|s| s * s).sum();
+    (sum_sq / frame.len() as f32).sqrt()
+}
+
+/// Index of the quietest frame boundary in `audio[from..to]`.
+fn quietest_point(audio: &[f32], from: usize, to: usize) -> usize {
+    let frame = ms_to_samples(FRAME_MS);
+    let mut best = to;
+    let mut best_rms = f32::MAX;
+
+    let mut pos = from;
+    while pos + frame <= to {
+        let rms = frame_rms(&audio[pos..pos + frame]);
+        if rms < best_rms {
+            best_rms = rms;
+            best = pos + frame / 2;
+        }
+        pos += frame;
+    }
+
+    best
+}
+
+/// Split `audio` into overlapping windows cut at silence boundaries.
+/// Short inputs come back as a single window.
+pub fn plan_windows(audio: &[f32]) -> Vec<Window> {
+    let len = audio.len();
+    if len <= ms_to_samples(SINGLE_PASS_MS) {
+        return vec![Window { start: 0, end: len }];
+    }
+
+    let min_window = ms_to_samples(MIN_WINDOW_MS);
+    let max_window = ms_to_samples(MAX_WINDOW_MS);
+    let overlap = ms_to_samples(OVERLAP_MS);
+
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        if len - start <= max_window {
+            windows.push(Window { start, end: len });
+            break;
+        }
+
+        let cut = quietest_point(audio, start + min_window, start + max_window);
+        windows.push(Window {
+            start,
+            end: (cut + overlap / 2).min(len),
+        });
+        start = cut.saturating_sub(overlap / 2);
+    }
+
+    windows
+}
+
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Number of leading words of `head` that repeat the trailing words of `tail`.
+fn seam_overlap(tail: &[String], head: &[String]) -> usize {
+    let max = tail.len().min(head.len()).min(MAX_SEAM_WORDS);
+    (MIN_SEAM_WORDS..=max)
+        .rev()
+        .find(|&k| tail[tail.len() - k..] == head[..k])
+        .unwrap_or(0)
+}
+
+/// Drop the first `count` words from the leading segments.
+fn drop_leading_words(segments: &mut Vec<Segment>, mut count: usize) {
+    while count > 0 && !segments.is_empty() {
+        let words: Vec<&str> = segments[0].text.split_whitespace().collect();
+        if words.len() <= count {
+            count -= words.len();
+            segments.remove(0);
+        } else {
+            segments[0].text = words[count..].join(" ");
+            count = 0;
+        }
+    }
+}
+
+/// Merge per-window results into one, shifting timestamps by each window's
+/// offset and removing the text duplicated by the overlaps.
+pub fn stitch(parts: Vec<(Window, TranscriptionResult)>, duration_ms: i64) -> TranscriptionResult {
+    let mut language = String::new();
+    let mut segments: Vec<Segment> = Vec::new();
+
+    for (window, part) in parts {
+        if language.is_empty() {
+            language = part.language.clone();
+        }
+
+        let offset = window.offset_ms();
+        let mut incoming: Vec<Segment> = part
+            .segments
+            .into_iter()
+            .filter(|s| !s.text.trim().is_empty())
+            .map(|s| Segment {
+                start_ms: s.start_ms + offset,
+                end_ms: s.end_ms + offset,
+                ..s
+            })
+            .collect();
+
+        let tail: Vec<String> = segments
+            .iter()
+            .rev()
+            .flat_map(|s| s.text.split_whitespace().rev().map(normalize_word))
+            .take(MAX_SEAM_WORDS)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let head: Vec<String> = incoming
+            .iter()
+            .flat_map(|s| s.text.split_whitespace().map(normalize_word))
+            .take(MAX_SEAM_WORDS)
+            .collect();
+
+        let duplicated = seam_overlap(&tail, &head);
+        if duplicated > 0 {
+            log::info!("Raccord de fenêtres : {} mot(s) en double retirés", duplicated);
+            drop_leading_words(&mut incoming, duplicated);
+        }
+
+        // Each window numbers speakers from 1; assume no turn at the seam and
+        // carry the previous window's last speaker over.
+        let last_speaker = segments.last().and_then(|s| s.speaker);
+        let first_speaker = incoming.first().and_then(|s| s.speaker);
+        if let (Some(last), Some(first)) = (last_speaker, first_speaker) {
+            if last != first {
+                for seg in &mut incoming {
+                    seg.speaker = seg.speaker.map(|s| 3 - s);
+                }
+            }
+        }
+
+        // Keep timestamps monotonic across the seam
+        let last_end = segments.last().map(|s| s.end_ms).unwrap_or(0);
+        for seg in &mut incoming {
+            seg.start_ms = seg.start_ms.max(last_end);
+            seg.end_ms = seg.end_ms.max(seg.start_ms);
+        }
+
+        segments.extend(incoming);
+    }
+
+    let text = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    TranscriptionResult {
+        text,
+        segments,
+        language,
+        duration_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{MockEngine, TranscriptionEngine};
+    use crate::transcription::TranscribeOptions;
+
+    /// `secs` of loud noise with a short silence every 5 s.
+    fn speech(secs: usize) -> Vec<f32> {
+        (0..secs * SAMPLE_RATE)
+            .map(|i| {
+                let in_pause = (i / SAMPLE_RATE) % 5 == 4 && i % SAMPLE_RATE < SAMPLE_RATE / 2;
+                if in_pause {
+                    0.0
+                } else {
+                    ((i * 7919 % 200) as f32 / 100.0 - 1.0) * 0.3
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn short_audio_is_one_window() {
+        let windows = plan_windows(&speech(10));
+        assert_eq!(windows.len(), 1);
+        assert_eq!((windows[0].start, windows[0].end), (0, 10 * SAMPLE_RATE));
+    }
+
+    #[test]
+    fn long_audio_is_cut_in_overlapping_windows() {
+        let audio = speech(70);
+        let windows = plan_windows(&audio);
+        assert!(windows.len() >= 3);
+        assert_eq!(windows[0].start, 0);
+        assert_eq!(windows.last().unwrap().end, audio.len());
+        for pair in windows.windows(2) {
+            assert!(pair[1].start < pair[0].end, "windows must overlap");
+            assert!(pair[0].end - pair[0].start <= ms_to_samples(MAX_WINDOW_MS + OVERLAP_MS));
+        }
+    }
+
+    #[test]
+    fn stitching_mock_windows_removes_the_overlap() {
+        let audio = speech(70);
+        let windows = plan_windows(&audio);
+        let script = [
+            "one two three four",
+            "three four five six",
+            "five six seven eight",
+            "seven eight nine ten",
+        ];
+        let engine = MockEngine::new(script[..windows.len()].iter().map(|s| s.to_string()).collect());
+        let options = TranscribeOptions {
+            language: "en".to_string(),
+            timestamps: true,
+            diarize: false,
+        };
+
+        let parts = windows
+            .iter()
+            .map(|w| (*w, engine.transcribe(&audio[w.start..w.end], &options, None).unwrap()))
+            .collect();
+        let result = stitch(parts, 70_000);
+
+        let expected = ["one two three four", "five six", "seven eight", "nine ten"];
+        assert_eq!(result.text, expected[..windows.len()].join(" "));
+        assert_eq!(result.segments[1].start_ms, windows[0].end as i64 * 1000 / SAMPLE_RATE as i64);
+        assert!(result.segments.windows(2).all(|s| s[0].end_ms <= s[1].start_ms));
+    }
+}
@@ -0,0 +1,247 @@
+//! Output sinks: extra destinations a dictation is sent to, in addition to or
+//! instead of the paste / type output. Each sink formats the text with its
+//! own template and reports its own failures.
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::active_window::ActiveWindow;
+use crate::config::{OutputSink, SinkTarget};
+use crate::errors::{AppError, AppResult};
+use crate::injection::{self, Session};
+use crate::rewrite;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Values available to sink templates besides the text itself.
+pub struct SinkContext<'a> {
+    pub language: &'a str,
+    pub model: &'a str,
+    pub window: Option<&'a ActiveWindow>,
+    pub time: DateTime<Local>,
+}
+
+/// Sent with the "sink-error" event when a sink fails.
+#[derive(Debug, Clone, Serialize)]
+pub struct SinkError {
+    pub sink: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+    timestamp: String,
+    language: &'a str,
+    model: &'a str,
+    window: Option<&'a str>,
+}
+
+fn default_format(target: &SinkTarget) -> &'static str {
+    match target {
+        SinkTarget::File { .. } => "[{timestamp}] {text}",
+        _ => "{text}",
+    }
+}
+
+/// Fill `{text}`, `{timestamp}`, `{date}`, `{time}`, `{language}`, `{model}`
+/// and `{window}` in `template`.
+pub fn render(template: &str, text: &str, ctx: &SinkContext) -> String {
+    let window = ctx.window.map(|w| w.class.as_str()).unwrap_or("");
+    template
+        .replace("{timestamp}", &ctx.time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .replace("{date}", &ctx.time.format("%Y-%m-%d").to_string())
+        .replace("{time}", &ctx.time.format("%H:%M:%S").to_string())
+        .replace("{language}", ctx.language)
+        .replace("{model}", ctx.model)
+        .replace("{window}", window)
+        // Last, so placeholders spoken in the dictation are left alone
+        .replace("{text}", text)
+}
+
+/// Sink path with its placeholders filled, e.g. "~/notes/{date}.md" for a
+/// daily file. A leading "~" is the home directory.
+fn resolve_path(path: &str, ctx: &SinkContext) -> PathBuf {
+    let path = render(path, "", ctx);
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub fn validate(sink: &OutputSink) -> AppResult<()> {
+    let invalid = |reason: &str| AppError::Sink(format!("{} : {}", sink.name, reason));
+
+    if sink.name.trim().is_empty() {
+        return Err(AppError::Sink("Nom de sortie vide".to_string()));
+    }
+    match &sink.target {
+        SinkTarget::File { path } | SinkTarget::NamedPipe { path } if path.trim().is_empty() => {
+            Err(invalid("chemin vide"))
+        }
+        SinkTarget::Webhook { url, allow_remote } => {
+            let url = reqwest::Url::parse(url)
+                .map_err(|e| invalid(&format!("URL invalide ({})", e)))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(invalid(&format!("schéma non supporté : {}", url.scheme())));
+            }
+            let host = url.host_str().unwrap_or("");
+            if !allow_remote && !rewrite::is_loopback(host) {
+                return Err(invalid(&format!(
+                    "hôte distant refusé : {} (autorisez les serveurs distants pour l'utiliser)",
+                    host
+                )));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn append_file(path: &str, entry: &str, ctx: &SinkContext) -> Result<(), String> {
+    let path = resolve_path(path, ctx);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("{} : {}", path.display(), e))?;
+    writeln!(file, "{}", entry).map_err(|e| e.to_string())
+}
+
+/// Write one line to a FIFO created beforehand with `mkfifo`. Fails instead of
+/// blocking when nothing reads the pipe.
+#[cfg(unix)]
+fn write_pipe(path: &str, entry: &str, ctx: &SinkContext) -> Result<(), String> {
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+
+    let path = resolve_path(path, ctx);
+    let metadata = std::fs::metadata(&path).map_err(|e| format!("{} : {}", path.display(), e))?;
+    if !metadata.file_type().is_fifo() {
+        return Err(format!("{} n'est pas un tube nommé", path.display()));
+    }
+    let mut pipe = std::fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(&path)
+        .map_err(|e| match e.raw_os_error() {
+            Some(libc::ENXIO) => format!("aucun lecteur sur {}", path.display()),
+            _ => format!("{} : {}", path.display(), e),
+        })?;
+    writeln!(pipe, "{}", entry).map_err(|e| e.to_string())
+}
+
+/// Windows named pipe, e.g. `\\.\pipe\dictation`.
+#[cfg(not(unix))]
+fn write_pipe(path: &str, entry: &str, ctx: &SinkContext) -> Result<(), String> {
+    let path = resolve_path(path, ctx);
+    let mut pipe = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("{} : {}", path.display(), e))?;
+    writeln!(pipe, "{}", entry).map_err(|e| e.to_string())
+}
+
+async fn post_webhook(url: &str, entry: &str, ctx: &SinkContext<'_>) -> Result<(), String> {
+    let client = rewrite::direct_client(WEBHOOK_TIMEOUT).map_err(|e| e.to_string())?;
+    let payload = WebhookPayload {
+        text: entry,
+        timestamp: ctx.time.to_rfc3339(),
+        language: ctx.language,
+        model: ctx.model,
+        window: ctx.window.map(|w| w.class.as_str()),
+    };
+    let response = client
+        .post(url)
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| format!("requête impossible : {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("réponse invalide (HTTP {})", response.status()));
+    }
+    Ok(())
+}
+
+/// The selection has to be served for as long as it is owned, so it is left
+/// to a tool that stays in the background.
+fn set_primary_selection(entry: &str) -> Result<(), String> {
+    if !cfg!(target_os = "linux") {
+        return Err("sélection primaire disponible sous Linux uniquement".to_string());
+    }
+    let tools: &[(&str, &[&str])] = match injection::detect_session() {
+        Session::Wayland => &[("wl-copy", &["--primary"])],
+        _ => &[
+            ("xclip", &["-selection", "primary"]),
+            ("xsel", &["--primary", "--input"]),
+        ],
+    };
+    let Some((program, args)) = tools.iter().find(|(program, _)| injection::in_path(program))
+    else {
+        let names: Vec<&str> = tools.iter().map(|(program, _)| *program).collect();
+        return Err(format!("installez {}", names.join(" ou ")));
+    };
+
+    let mut child = Command::new(program)
+        .args(*args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("{} : {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(entry.as_bytes())
+            .map_err(|e| format!("{} : {}", program, e))?;
+    }
+    // The tool forks to keep the selection, so this returns right away
+    let status = child.wait().map_err(|e| format!("{} : {}", program, e))?;
+    if !status.success() {
+        return Err(format!("{} : {}", program, status));
+    }
+    Ok(())
+}
+
+/// Send `text` to one sink, formatted with its template.
+pub async fn send(sink: &OutputSink, text: &str, ctx: &SinkContext<'_>) -> AppResult<()> {
+    validate(sink)?;
+
+    let format = sink
+        .format
+        .as_deref()
+        .unwrap_or_else(|| default_format(&sink.target));
+    let entry = render(format, text, ctx);
+
+    let sent = match &sink.target {
+        SinkTarget::File { path } => append_file(path, &entry, ctx),
+        SinkTarget::NamedPipe { path } => write_pipe(path, &entry, ctx),
+        SinkTarget::Webhook { url, .. } => post_webhook(url, &entry, ctx).await,
+        SinkTarget::PrimarySelection => set_primary_selection(&entry),
+    };
+    sent.map_err(|e| AppError::Sink(format!("{} : {}", sink.name, e)))
+}
+
+/// Send `text` to every enabled sink. A failing sink is reported and does not
+/// stop the others.
+pub async fn dispatch(
+    sinks: &[OutputSink],
+    text: &str,
+    ctx: &SinkContext<'_>,
+    report: &dyn Fn(SinkError),
+) {
+    for sink in sinks.iter().filter(|s| s.enabled) {
+        if let Err(e) = send(sink, text, ctx).await {
+            report(SinkError {
+                sink: sink.name.clone(),
+                message: e.to_string(),
+            });
+        }
+    }
+}
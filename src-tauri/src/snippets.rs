@@ -0,0 +1,234 @@
+//! Personal text snippets: a spoken trigger ("insert my signature") expands to
+//! stored text. Templates may use `{date}`, `{time}`, `{datetime}`,
+//! `{weekday}` and `{clipboard}`; date variables accept a strftime format,
+//! e.g. `{date:%d/%m/%Y}`.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::errors::{AppError, AppResult};
+use crate::voice_commands::{phrase_keys, split_words};
+
+const SNIPPETS_FILE: &str = "snippets.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: String,
+    /// Spoken phrase, matched case- and punctuation-insensitively
+    pub trigger: String,
+    /// Template inserted in place of the trigger
+    pub text: String,
+}
+
+pub fn load_snippets(app_data_dir: &Path) -> AppResult<Vec<Snippet>> {
+    let path = app_data_dir.join(SNIPPETS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| AppError::Config(format!("Lecture impossible : {}", e)))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Config(format!("Parsing impossible : {}", e)))
+}
+
+fn save_snippets(app_data_dir: &Path, snippets: &[Snippet]) -> AppResult<()> {
+    std::fs::create_dir_all(app_data_dir)?;
+    let json = serde_json::to_string_pretty(snippets)
+        .map_err(|e| AppError::Config(format!("Sérialisation impossible : {}", e)))?;
+    std::fs::write(app_data_dir.join(SNIPPETS_FILE), json)?;
+    Ok(())
+}
+
+/// Two snippets cannot share a trigger, and a trigger needs at least one word.
+fn check_trigger(snippets: &[Snippet], trigger: &str, id: Option<&str>) -> AppResult<()> {
+    let keys = phrase_keys(trigger);
+    if keys.is_empty() {
+        return Err(AppError::Config("Déclencheur vide".into()));
+    }
+    if snippets
+        .iter()
+        .any(|s| Some(s.id.as_str()) != id && phrase_keys(&s.trigger) == keys)
+    {
+        return Err(AppError::Config(format!("Déclencheur déjà utilisé : {}", trigger)));
+    }
+    Ok(())
+}
+
+pub fn add_snippet(app_data_dir: &Path, trigger: String, text: String) -> AppResult<Snippet> {
+    let mut snippets = load_snippets(app_data_dir)?;
+    check_trigger(&snippets, &trigger, None)?;
+
+    let id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis().to_string())
+        .unwrap_or_default();
+    let snippet = Snippet { id, trigger, text };
+    snippets.push(snippet.clone());
+    save_snippets(app_data_dir, &snippets)?;
+    Ok(snippet)
+}
+
+pub fn update_snippet(app_data_dir: &Path, snippet: Snippet) -> AppResult<()> {
+    let mut snippets = load_snippets(app_data_dir)?;
+    check_trigger(&snippets, &snippet.trigger, Some(&snippet.id))?;
+
+    let existing = snippets
+        .iter_mut()
+        .find(|s| s.id == snippet.id)
+        .ok_or_else(|| AppError::Config(format!("Snippet introuvable : {}", snippet.id)))?;
+    *existing = snippet;
+    save_snippets(app_data_dir, &snippets)
+}
+
+pub fn delete_snippet(app_data_dir: &Path, id: &str) -> AppResult<()> {
+    let mut snippets = load_snippets(app_data_dir)?;
+    let before = snippets.len();
+    snippets.retain(|s| s.id != id);
+    if snippets.len() == before {
+        return Err(AppError::Config(format!("Snippet introuvable : {}", id)));
+    }
+    save_snippets(app_data_dir, &snippets)
+}
+
+/// Current local time with a strftime format; an invalid format leaves the
+/// variable untouched.
+fn format_now(format: &str) -> Option<String> {
+    let mut out = String::new();
+    write!(out, "{}", Local::now().format(format)).ok()?;
+    Some(out)
+}
+
+fn render_variable(name: &str, format: Option<&str>, clipboard: &dyn Fn() -> Option<String>) -> Option<String> {
+    match name {
+        "date" => format_now(format.unwrap_or("%Y-%m-%d")),
+        "time" => format_now(format.unwrap_or("%H:%M")),
+        "datetime" => format_now(format.unwrap_or("%Y-%m-%d %H:%M")),
+        "weekday" => format_now(format.unwrap_or("%A")),
+        "clipboard" => clipboard(),
+        _ => None,
+    }
+}
+
+/// Replace `{variable}` and `{variable:format}` placeholders. Unknown
+/// placeholders are kept as written.
+pub fn render(template: &str, clipboard: &dyn Fn() -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeholder = &after[..end];
+        let (name, format) = match placeholder.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format)),
+            None => (placeholder.trim(), None),
+        };
+        match render_variable(name, format, clipboard) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Expand every snippet trigger found in `text`. Everything between the
+/// triggers, line breaks included, is kept as it was.
+pub fn expand(text: &str, snippets: &[Snippet], clipboard: &dyn Fn() -> Option<String>) -> String {
+    if snippets.is_empty() {
+        return text.to_string();
+    }
+
+    let words = split_words(text);
+    // Longest triggers first so "insert my signature" wins over "insert my"
+    let mut triggers: Vec<(Vec<String>, &Snippet)> = snippets
+        .iter()
+        .map(|s| (phrase_keys(&s.trigger), s))
+        .filter(|(keys, _)| !keys.is_empty())
+        .collect();
+    triggers.sort_by_key(|(keys, _)| std::cmp::Reverse(keys.len()));
+
+    let mut out = String::with_capacity(text.len());
+    // End of the part of `text` already copied to `out`
+    let mut copied = 0;
+    let mut i = 0;
+    while i < words.len() {
+        let matched = triggers.iter().find(|(keys, _)| {
+            i + keys.len() <= words.len()
+                && keys.iter().zip(&words[i..]).all(|(k, w)| *k == w.key)
+        });
+
+        let Some((keys, snippet)) = matched else {
+            i += 1;
+            continue;
+        };
+        let last = &words[i + keys.len() - 1];
+        out.push_str(&text[copied..words[i].start]);
+        log::info!("Snippet inséré : {}", snippet.trigger);
+        out.push_str(&render(&snippet.text, clipboard));
+        copied = last.start + last.text.len();
+        i += keys.len();
+    }
+
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// Load the snippets from `app_data_dir` and expand them in `text`.
+pub fn apply(text: &str, app_data_dir: &Path, clipboard: &dyn Fn() -> Option<String>) -> String {
+    match load_snippets(app_data_dir) {
+        Ok(snippets) => expand(text, &snippets, clipboard),
+        Err(e) => {
+            log::error!("Snippets illisibles : {}", e);
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(trigger: &str, text: &str) -> Snippet {
+        Snippet {
+            id: trigger.to_string(),
+            trigger: trigger.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn keeps_line_breaks_around_triggers() {
+        let snippets = [snippet("insert my signature", "Jane Doe")];
+        let text = "Thanks,\n\nInsert my signature.\nP.S. see you";
+        assert_eq!(
+            expand(text, &snippets, &|| None),
+            "Thanks,\n\nJane Doe\nP.S. see you"
+        );
+    }
+
+    #[test]
+    fn text_without_triggers_is_unchanged() {
+        let snippets = [snippet("insert my signature", "Jane Doe")];
+        let text = "Line one\n  line two\tend";
+        assert_eq!(expand(text, &snippets, &|| None), text);
+    }
+
+    #[test]
+    fn longest_trigger_wins() {
+        let snippets = [snippet("my address", "short"), snippet("my address in full", "long")];
+        assert_eq!(expand("Here is my address in full", &snippets, &|| None), "Here is long");
+    }
+}
@@ -1,13 +1,22 @@
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use whisper_rs::WhisperContext;
 
+use crate::active_window::ActiveWindow;
 use crate::config::AppConfig;
+use crate::continuation::Insertion;
+use crate::engine::TranscriptionEngine;
 
 pub struct InnerState {
     pub config: AppConfig,
     pub app_data_dir: PathBuf,
-    pub whisper_ctx: Option<Arc<WhisperContext>>,
+    pub engine: Option<Arc<dyn TranscriptionEngine>>,
+    /// Engine for a model requested by an application profile, with its id
+    pub profile_engine: Option<(String, Arc<dyn TranscriptionEngine>)>,
+    /// Window focused when the current dictation started
+    pub target_window: Option<ActiveWindow>,
+    /// Dictations pasted or typed, most recent last: the next one is joined
+    /// to the top, and undo erases from the top
+    pub insertions: Vec<Insertion>,
     pub is_recording: bool,
     pub audio_buffer: Vec<f32>,
     pub sample_rate: u32,
@@ -26,7 +35,10 @@ impl AppState {
             inner: Arc::new(Mutex::new(InnerState {
                 config,
                 app_data_dir,
-                whisper_ctx: None,
+                engine: None,
+                profile_engine: None,
+                target_window: None,
+                insertions: Vec::new(),
                 is_recording: false,
                 audio_buffer: Vec::new(),
                 sample_rate: 16000,
@@ -1,55 +1,490 @@
 use serde::Serialize;
+use std::path::Path;
 use sysinfo::System;
 
+use crate::benchmark::{self, BenchmarkResult};
+use crate::models::{self, ModelInfo};
+use crate::transcription;
+
+/// A model is considered comfortable for dictation when it transcribes the
+/// reference clip at least twice as fast as real time.
+const MAX_REAL_TIME_FACTOR: f64 = 0.5;
+/// Free memory kept aside for the rest of the system when sizing a model.
+const MEMORY_HEADROOM: f64 = 1.5;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SystemInfo {
     pub total_ram_gb: f64,
+    pub available_ram_gb: f64,
     pub cpu_cores: usize,
+    /// SIMD extensions whisper.cpp can use: avx2, avx512, neon
+    pub simd: Vec<String>,
+    /// First non-software Vulkan device, if any
+    pub vulkan_device: Option<String>,
     pub os: String,
     pub arch: String,
+    /// Compute backend compiled into this build: metal, vulkan, openblas or cpu
+    pub backend: String,
     pub recommended_model: String,
-    pub recommended_model_reason: String,
+    pub recommendation_reasons: Vec<RecommendationReason>,
+}
+
+/// Why a model was recommended. Rendered by the frontend in the UI language.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecommendationReason {
+    /// Measured with `benchmark_models`
+    Benchmark { real_time_factor: f64 },
+    /// Every measured model is slower than the dictation target
+    BenchmarkTooSlow { real_time_factor: f64 },
+    AvailableMemory { available_gb: f64, total_gb: f64 },
+    Cpu { cores: usize, simd: Vec<String> },
+    Gpu { backend: String, device: String },
+    NoGpu { backend: String },
+    /// The model size is capped by free memory rather than compute
+    LimitedByMemory,
+    /// The model size is capped by CPU/GPU throughput rather than memory
+    LimitedByCompute,
+    /// The full-precision model does not fit, its quantized variant does
+    Quantized,
+    EnglishOnly,
+}
+
+/// One model size, with the memory whisper.cpp needs to run it.
+struct Tier {
+    model: &'static str,
+    english: Option<&'static str>,
+    quantized: &'static str,
+    memory_mb: f64,
+    quantized_memory_mb: f64,
+    /// Compute score (cores weighted by SIMD support) needed for dictation speed
+    min_compute: f64,
+}
+
+const TIERS: &[Tier] = &[
+    Tier { model: "ggml-tiny",     english: Some("ggml-tiny.en"),   quantized: "ggml-tiny-q5_1",     memory_mb: 273.0,  quantized_memory_mb: 150.0,  min_compute: 0.0 },
+    Tier { model: "ggml-base",     english: Some("ggml-base.en"),   quantized: "ggml-base-q5_1",     memory_mb: 388.0,  quantized_memory_mb: 220.0,  min_compute: 2.0 },
+    Tier { model: "ggml-small",    english: Some("ggml-small.en"),  quantized: "ggml-small-q5_1",    memory_mb: 852.0,  quantized_memory_mb: 420.0,  min_compute: 6.0 },
+    Tier { model: "ggml-medium",   english: Some("ggml-medium.en"), quantized: "ggml-medium-q5_0",   memory_mb: 2100.0, quantized_memory_mb: 1000.0, min_compute: 16.0 },
+    Tier { model: "ggml-large-v3", english: None,                   quantized: "ggml-large-v3-q5_0", memory_mb: 3900.0, quantized_memory_mb: 1700.0, min_compute: 32.0 },
+];
+
+/// Compute score given to a usable GPU: enough for every tier.
+const GPU_COMPUTE: f64 = 64.0;
+
+/// Hardware facts the recommendation is based on.
+struct Hardware {
+    total_gb: f64,
+    available_gb: f64,
+    cores: usize,
+    simd: Vec<String>,
+    gpu: Option<String>,
+    backend: String,
+    /// `backend`, or "cpu" when `force_cpu` keeps a GPU backend off the GPU
+    active_backend: String,
 }
 
-pub fn get_system_info() -> SystemInfo {
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn detect_simd() -> Vec<String> {
+    #[allow(unused_mut)]
+    let mut simd = Vec::new();
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            simd.push("avx2".to_string());
+        }
+        if std::is_x86_feature_detected!("avx512f") {
+            simd.push("avx512".to_string());
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            simd.push("neon".to_string());
+        }
+    }
+    simd
+}
+
+/// Name of the best Vulkan device reported by `vulkaninfo`, skipping
+/// software rasterizers (llvmpipe, SwiftShader).
+fn detect_vulkan_device() -> Option<String> {
+    if cfg!(target_os = "macos") {
+        return None;
+    }
+
+    let output = std::process::Command::new("vulkaninfo")
+        .arg("--summary")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut devices: Vec<(bool, String)> = Vec::new();
+    let mut device_type = "";
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "deviceType" => device_type = value.trim(),
+            "deviceName" if device_type != "PHYSICAL_DEVICE_TYPE_CPU" => {
+                let discrete = device_type == "PHYSICAL_DEVICE_TYPE_DISCRETE_GPU";
+                devices.push((discrete, value.trim().to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    // Discrete GPUs first
+    devices.sort_by_key(|(discrete, _)| !discrete);
+    devices.into_iter().next().map(|(_, name)| name)
+}
+
+/// GPU the compiled backend will actually run on.
+fn usable_gpu(
+    backend: &str,
+    vulkan_device: Option<&String>,
+    force_cpu: bool,
+) -> Option<String> {
+    if force_cpu {
+        return None;
+    }
+    match backend {
+        "metal" if cfg!(target_arch = "aarch64") => Some("Apple GPU".to_string()),
+        "vulkan" => vulkan_device.cloned(),
+        _ => None,
+    }
+}
+
+/// Cores weighted by the widest SIMD extension available.
+fn compute_score(hw: &Hardware) -> f64 {
+    if hw.gpu.is_some() {
+        return GPU_COMPUTE;
+    }
+    let simd_factor = if hw.simd.iter().any(|s| s == "avx512") {
+        1.5
+    } else if !hw.simd.is_empty() {
+        1.0
+    } else {
+        0.5
+    };
+    hw.cores as f64 * simd_factor
+}
+
+fn hardware_reasons(hw: &Hardware) -> Vec<RecommendationReason> {
+    let mut reasons = vec![
+        RecommendationReason::AvailableMemory {
+            available_gb: round1(hw.available_gb),
+            total_gb: round1(hw.total_gb),
+        },
+        RecommendationReason::Cpu {
+            cores: hw.cores,
+            simd: hw.simd.clone(),
+        },
+    ];
+    reasons.push(match &hw.gpu {
+        Some(device) => RecommendationReason::Gpu {
+            backend: hw.backend.clone(),
+            device: device.clone(),
+        },
+        None => RecommendationReason::NoGpu {
+            backend: hw.backend.clone(),
+        },
+    });
+    reasons
+}
+
+pub fn get_system_info(app_data_dir: &Path, language: &str, force_cpu: bool) -> SystemInfo {
     let sys = System::new_all();
-    let ram_gb = sys.total_memory() as f64 / 1_073_741_824.0;
+    let total_gb = sys.total_memory() as f64 / 1_073_741_824.0;
+    let available_gb = sys.available_memory() as f64 / 1_073_741_824.0;
     let cpu_cores = sys.cpus().len();
-    let os = std::env::consts::OS.to_string();
-    let arch = std::env::consts::ARCH.to_string();
+    let backend = transcription::compiled_backend().to_string();
+    let vulkan_device = detect_vulkan_device();
+
+    let hw = Hardware {
+        total_gb,
+        available_gb,
+        cores: sys.physical_core_count().unwrap_or(cpu_cores),
+        simd: detect_simd(),
+        gpu: usable_gpu(&backend, vulkan_device.as_ref(), force_cpu),
+        backend: backend.clone(),
+        active_backend: transcription::active_backend(force_cpu).to_string(),
+    };
 
-    let (model, reason) = recommend_model(ram_gb);
+    let results = benchmark::load_results(app_data_dir);
+    let catalog = models::list_models(app_data_dir);
+    let (model, reasons) = recommend_from_benchmarks(&results, &catalog, &hw, language)
+        .unwrap_or_else(|| recommend_model(&hw, language));
 
     SystemInfo {
-        total_ram_gb: (ram_gb * 10.0).round() / 10.0,
+        total_ram_gb: round1(total_gb),
+        available_ram_gb: round1(available_gb),
         cpu_cores,
-        os,
-        arch,
-        recommended_model: model.to_string(),
-        recommended_model_reason: reason.to_string(),
-    }
-}
-
-fn recommend_model(ram_gb: f64) -> (&'static str, &'static str) {
-    if ram_gb < 4.0 {
-        (
-            "ggml-tiny-q5_1",
-            "Votre machine a moins de 4 Go de RAM. Le modèle Tiny quantisé est recommandé pour des performances optimales.",
-        )
-    } else if ram_gb < 8.0 {
-        (
-            "ggml-base",
-            "Avec 4 à 8 Go de RAM, le modèle Base offre un bon équilibre entre précision et performance.",
-        )
-    } else if ram_gb < 16.0 {
-        (
-            "ggml-small",
-            "Avec 8 à 16 Go de RAM, le modèle Small offre une bonne précision sans surcharger votre machine.",
-        )
+        simd: hw.simd.clone(),
+        vulkan_device,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        backend,
+        recommended_model: model,
+        recommendation_reasons: reasons,
+    }
+}
+
+/// Pick the largest benchmarked model that still runs fast enough on this
+/// machine. Only results that still apply are considered: the model is
+/// downloaded, suits `language`, fits in free memory and was measured on the
+/// current backend. `None` when no such result exists.
+fn recommend_from_benchmarks(
+    results: &[BenchmarkResult],
+    catalog: &[ModelInfo],
+    hw: &Hardware,
+    language: &str,
+) -> Option<(String, Vec<RecommendationReason>)> {
+    let available_mb = hw.available_gb * 1024.0;
+    let usable: Vec<(&BenchmarkResult, &ModelInfo)> = results
+        .iter()
+        .filter(|r| r.backend == hw.active_backend)
+        .filter_map(|r| {
+            let model = catalog.iter().find(|m| m.id == r.model_id)?;
+            // A model needs at least its weights in memory, even when the
+            // measured growth was smaller (mapped pages already resident)
+            let memory_mb = r.peak_memory_mb.max(model.size_bytes as f64 / 1_048_576.0);
+            let usable = model.is_downloaded
+                && (!model.is_english_only || language == "en")
+                && memory_mb * MEMORY_HEADROOM <= available_mb;
+            usable.then_some((r, model))
+        })
+        .collect();
+
+    let mut reasons = hardware_reasons(hw);
+    let fast_enough = usable
+        .iter()
+        .filter(|(r, _)| r.real_time_factor <= MAX_REAL_TIME_FACTOR)
+        .max_by_key(|(_, m)| m.size_bytes);
+
+    match fast_enough {
+        Some((best, _)) => {
+            reasons.push(RecommendationReason::Benchmark {
+                real_time_factor: best.real_time_factor,
+            });
+            Some((best.model_id.clone(), reasons))
+        }
+        None => {
+            let (fastest, _) = usable
+                .iter()
+                .min_by(|a, b| a.0.real_time_factor.total_cmp(&b.0.real_time_factor))?;
+            reasons.push(RecommendationReason::BenchmarkTooSlow {
+                real_time_factor: fastest.real_time_factor,
+            });
+            Some((fastest.model_id.clone(), reasons))
+        }
+    }
+}
+
+/// Largest tier allowed by both free memory and compute throughput, using the
+/// quantized variant when only that one fits, and the English-only variant
+/// when dictating in English.
+fn recommend_model(hw: &Hardware, language: &str) -> (String, Vec<RecommendationReason>) {
+    let mut reasons = hardware_reasons(hw);
+    let available_mb = hw.available_gb * 1024.0;
+    let fits = |memory_mb: f64| memory_mb * MEMORY_HEADROOM <= available_mb;
+
+    let compute = compute_score(hw);
+    let compute_tier = TIERS
+        .iter()
+        .rposition(|t| t.min_compute <= compute)
+        .unwrap_or(0);
+    let full_tier = TIERS.iter().rposition(|t| fits(t.memory_mb));
+    let quantized_tier = TIERS.iter().rposition(|t| fits(t.quantized_memory_mb));
+
+    let memory_tier = quantized_tier.unwrap_or(0);
+    let index = compute_tier.min(memory_tier);
+    reasons.push(if memory_tier < compute_tier {
+        RecommendationReason::LimitedByMemory
     } else {
-        (
-            "ggml-medium",
-            "Avec plus de 16 Go de RAM, le modèle Medium offre une excellente précision de transcription.",
-        )
+        RecommendationReason::LimitedByCompute
+    });
+
+    let tier = &TIERS[index];
+    let quantized = full_tier.map(|full| full < index).unwrap_or(true);
+    if quantized {
+        reasons.push(RecommendationReason::Quantized);
+        return (tier.quantized.to_string(), reasons);
+    }
+
+    match tier.english {
+        Some(english) if language == "en" => {
+            reasons.push(RecommendationReason::EnglishOnly);
+            (english.to_string(), reasons)
+        }
+        _ => (tier.model.to_string(), reasons),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hardware(available_gb: f64, cores: usize, gpu: bool) -> Hardware {
+        Hardware {
+            total_gb: 16.0,
+            available_gb,
+            cores,
+            simd: vec!["avx2".to_string()],
+            gpu: gpu.then(|| "Test GPU".to_string()),
+            backend: "vulkan".to_string(),
+            active_backend: "vulkan".to_string(),
+        }
+    }
+
+    fn model(id: &str, size_mb: u64, english_only: bool, downloaded: bool) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            size_bytes: size_mb * 1_048_576,
+            size_label: String::new(),
+            url: String::new(),
+            is_english_only: english_only,
+            is_quantized: id.contains("-q"),
+            supports_diarization: false,
+            is_downloaded: downloaded,
+        }
+    }
+
+    fn result(id: &str, real_time_factor: f64) -> BenchmarkResult {
+        BenchmarkResult {
+            model_id: id.to_string(),
+            load_ms: 100,
+            transcribe_ms: 1000,
+            real_time_factor,
+            peak_memory_mb: 0.0,
+            backend: "vulkan".to_string(),
+            measured_at: 0,
+        }
+    }
+
+    fn catalog() -> Vec<ModelInfo> {
+        vec![
+            model("ggml-base", 142, false, true),
+            model("ggml-small", 466, false, true),
+            model("ggml-small.en", 466, true, true),
+            model("ggml-medium", 1500, false, true),
+            model("ggml-large-v3", 2950, false, false),
+        ]
+    }
+
+    fn has(reasons: &[RecommendationReason], check: fn(&RecommendationReason) -> bool) -> bool {
+        reasons.iter().any(check)
+    }
+
+    #[test]
+    fn english_dictation_gets_the_english_only_variant() {
+        let hw = hardware(12.0, 8, false);
+
+        let (en, reasons) = recommend_model(&hw, "en");
+        assert_eq!(en, "ggml-small.en");
+        assert!(has(&reasons, |r| matches!(r, RecommendationReason::EnglishOnly)));
+        assert!(has(&reasons, |r| matches!(r, RecommendationReason::LimitedByCompute)));
+
+        let (fr, reasons) = recommend_model(&hw, "fr");
+        assert_eq!(fr, "ggml-small");
+        assert!(!has(&reasons, |r| matches!(r, RecommendationReason::EnglishOnly)));
+    }
+
+    #[test]
+    fn low_memory_falls_back_to_a_quantized_model() {
+        let (model, reasons) = recommend_model(&hardware(0.5, 8, false), "fr");
+        assert_eq!(model, "ggml-base-q5_1");
+        assert!(has(&reasons, |r| matches!(r, RecommendationReason::LimitedByMemory)));
+        assert!(has(&reasons, |r| matches!(r, RecommendationReason::Quantized)));
+
+        let (model, _) = recommend_model(&hardware(0.1, 8, false), "en");
+        assert_eq!(model, "ggml-tiny-q5_1");
+    }
+
+    #[test]
+    fn gpu_allows_the_largest_model() {
+        let (model, _) = recommend_model(&hardware(32.0, 4, true), "en");
+        assert_eq!(model, "ggml-large-v3");
+    }
+
+    #[test]
+    fn benchmark_picks_the_largest_fast_model() {
+        let results = [
+            result("ggml-base", 0.05),
+            result("ggml-small", 0.2),
+            result("ggml-medium", 0.9),
+        ];
+        let (model, reasons) =
+            recommend_from_benchmarks(&results, &catalog(), &hardware(12.0, 8, false), "fr")
+                .unwrap();
+        assert_eq!(model, "ggml-small");
+        assert!(has(&reasons, |r| matches!(r, RecommendationReason::Benchmark { .. })));
+    }
+
+    #[test]
+    fn benchmark_skips_english_only_models_for_other_languages() {
+        let results = [result("ggml-base", 0.05), result("ggml-small.en", 0.2)];
+        let hw = hardware(12.0, 8, false);
+
+        let (fr, _) = recommend_from_benchmarks(&results, &catalog(), &hw, "fr").unwrap();
+        assert_eq!(fr, "ggml-base");
+        let (auto, _) = recommend_from_benchmarks(&results, &catalog(), &hw, "auto").unwrap();
+        assert_eq!(auto, "ggml-base");
+        let (en, _) = recommend_from_benchmarks(&results, &catalog(), &hw, "en").unwrap();
+        assert_eq!(en, "ggml-small.en");
+    }
+
+    #[test]
+    fn benchmark_skips_models_that_no_longer_fit() {
+        let mut medium = result("ggml-medium", 0.3);
+        medium.peak_memory_mb = 2100.0;
+        let results = [result("ggml-base", 0.05), medium];
+
+        let (model, _) =
+            recommend_from_benchmarks(&results, &catalog(), &hardware(2.0, 8, false), "fr")
+                .unwrap();
+        assert_eq!(model, "ggml-base");
+    }
+
+    #[test]
+    fn benchmark_ignores_deleted_models_and_other_backends() {
+        let mut cpu = result("ggml-medium", 0.3);
+        cpu.backend = "cpu".to_string();
+        let results = [result("ggml-large-v3", 0.1), cpu, result("ggml-base", 0.05)];
+
+        let (model, _) =
+            recommend_from_benchmarks(&results, &catalog(), &hardware(12.0, 8, false), "fr")
+                .unwrap();
+        assert_eq!(model, "ggml-base");
+    }
+
+    #[test]
+    fn no_usable_result_falls_back_to_hardware() {
+        let results = [result("ggml-large-v3", 0.1), result("ggml-small.en", 0.2)];
+        assert!(
+            recommend_from_benchmarks(&results, &catalog(), &hardware(12.0, 8, false), "fr")
+                .is_none()
+        );
+        assert!(
+            recommend_from_benchmarks(&[], &catalog(), &hardware(12.0, 8, false), "fr").is_none()
+        );
+    }
+
+    #[test]
+    fn benchmark_too_slow_recommends_the_fastest() {
+        let results = [result("ggml-small", 0.8), result("ggml-base", 0.6)];
+        let (model, reasons) =
+            recommend_from_benchmarks(&results, &catalog(), &hardware(12.0, 8, false), "fr")
+                .unwrap();
+        assert_eq!(model, "ggml-base");
+        assert!(has(&reasons, |r| matches!(r, RecommendationReason::BenchmarkTooSlow { .. })));
     }
 }
@@ -1,25 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::path::Path;
-use std::sync::Arc;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
 
+use crate::engine::{TranscriptionEngine, WhisperEngine};
+#[cfg(feature = "mock-engine")]
+use crate::engine::MockEngine;
 use crate::errors::{AppError, AppResult};
+use crate::models;
+use crate::segmentation;
+use crate::state::InnerState;
 
-pub fn load_model(path: &Path) -> AppResult<Arc<WhisperContext>> {
+/// A timed piece of transcript, as produced by Whisper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+    /// Mean token probability of the segment, between 0 and 1
+    #[serde(default)]
+    pub confidence: f32,
+    /// Speaker label (1 or 2), only set when diarization is enabled
+    #[serde(default)]
+    pub speaker: Option<u32>,
+}
+
+/// Full result of a transcription: the joined text plus its timed segments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    pub text: String,
+    pub segments: Vec<Segment>,
+    pub language: String,
+    pub duration_ms: i64,
+}
+
+/// Per-call decoding options.
+#[derive(Debug, Clone)]
+pub struct TranscribeOptions {
+    pub language: String,
+    /// Per-segment timing (slower; dictation does not need it)
+    pub timestamps: bool,
+    /// Speaker-turn detection, for tinydiarize models only
+    pub diarize: bool,
+}
+
+/// Compute backend compiled into this build (see the cargo features).
+pub fn compiled_backend() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "metal"
+    } else if cfg!(feature = "vulkan") {
+        "vulkan"
+    } else if cfg!(feature = "openblas") {
+        "openblas"
+    } else {
+        "cpu"
+    }
+}
+
+/// Backend inference actually runs on: `force_cpu` keeps GPU backends on the
+/// CPU.
+pub fn active_backend(force_cpu: bool) -> &'static str {
+    match compiled_backend() {
+        "metal" | "vulkan" if force_cpu => "cpu",
+        backend => backend,
+    }
+}
+
+/// Load a model, on the GPU unless `force_cpu` is set. A failed GPU
+/// initialisation (missing driver, no usable device) retries on the CPU.
+pub fn load_model(path: &Path, force_cpu: bool) -> AppResult<Arc<dyn TranscriptionEngine>> {
     let path_str = path
         .to_str()
         .ok_or_else(|| AppError::Transcription("Chemin du modèle invalide".into()))?;
 
-    let ctx = WhisperContext::new_with_params(path_str, WhisperContextParameters::default())
-        .map_err(|e| AppError::Transcription(format!("Chargement du modèle impossible : {}", e)))?;
+    let mut params = WhisperContextParameters::default();
+    params.use_gpu(!force_cpu);
+
+    let ctx = match WhisperContext::new_with_params(path_str, params) {
+        Ok(ctx) => {
+            log::info!(
+                "Modèle chargé (backend {}, GPU {})",
+                compiled_backend(),
+                if force_cpu { "désactivé" } else { "activé" }
+            );
+            ctx
+        }
+        Err(e) if !force_cpu => {
+            log::warn!("Initialisation GPU échouée ({}), nouvel essai sur CPU", e);
+            let mut cpu_params = WhisperContextParameters::default();
+            cpu_params.use_gpu(false);
+            WhisperContext::new_with_params(path_str, cpu_params).map_err(|e| {
+                AppError::Transcription(format!("Chargement du modèle impossible : {}", e))
+            })?
+        }
+        Err(e) => {
+            return Err(AppError::Transcription(format!(
+                "Chargement du modèle impossible : {}",
+                e
+            )))
+        }
+    };
+
+    Ok(Arc::new(WhisperEngine::new(ctx)))
+}
+
+/// With the `mock-engine` feature, points to a file of scripted transcripts
+/// used instead of a real model.
+const MOCK_SCRIPT_VAR: &str = "LOCALWHISPER_MOCK_SCRIPT";
+
+fn mock_enabled() -> bool {
+    cfg!(feature = "mock-engine") && std::env::var_os(MOCK_SCRIPT_VAR).is_some()
+}
+
+#[cfg(feature = "mock-engine")]
+fn mock_engine() -> AppResult<Option<Arc<dyn TranscriptionEngine>>> {
+    match std::env::var_os(MOCK_SCRIPT_VAR) {
+        Some(path) => Ok(Some(Arc::new(MockEngine::from_file(Path::new(&path))?))),
+        None => Ok(None),
+    }
+}
 
-    Ok(Arc::new(ctx))
+#[cfg(not(feature = "mock-engine"))]
+fn mock_engine() -> AppResult<Option<Arc<dyn TranscriptionEngine>>> {
+    Ok(None)
 }
 
-pub fn transcribe(ctx: &WhisperContext, audio: &[f32], language: &str) -> AppResult<String> {
+/// Return the cached engine, lazily loading the active model from disk.
+pub fn ensure_model(
+    inner_arc: &Arc<Mutex<InnerState>>,
+    app_data_dir: &Path,
+) -> AppResult<Arc<dyn TranscriptionEngine>> {
+    let (engine, model_id, force_cpu) = {
+        let inner = inner_arc.lock().unwrap();
+        (
+            inner.engine.clone(),
+            inner.config.active_model.clone(),
+            inner.config.force_cpu,
+        )
+    };
+
+    if let Some(engine) = engine {
+        return Ok(engine);
+    }
+
+    let engine = match mock_engine()? {
+        Some(engine) => engine,
+        None => {
+            let model_id = model_id.ok_or_else(|| {
+                AppError::Transcription(
+                    "Aucun modèle sélectionné. Veuillez configurer un modèle dans les paramètres.".into(),
+                )
+            })?;
+
+            let path = models::get_model_path(app_data_dir, &model_id).ok_or_else(|| {
+                AppError::Transcription(
+                    "Aucun modèle installé. Veuillez télécharger un modèle dans les paramètres.".into(),
+                )
+            })?;
+
+            load_model(&path, force_cpu)?
+        }
+    };
+
+    log::info!("Moteur de transcription chargé : {}", engine.name());
+    let mut inner = inner_arc.lock().unwrap();
+    inner.engine = Some(engine.clone());
+    Ok(engine)
+}
+
+/// Engine for `model_id` when an application profile asks for a model other
+/// than the active one. It is cached next to the main engine so switching
+/// between applications does not reload either.
+pub fn ensure_profile_model(
+    inner_arc: &Arc<Mutex<InnerState>>,
+    app_data_dir: &Path,
+    model_id: &str,
+) -> AppResult<Arc<dyn TranscriptionEngine>> {
+    // The mock stands in for every model: profiles share the main instance,
+    // and with it the position in the script
+    if mock_enabled() {
+        return ensure_model(inner_arc, app_data_dir);
+    }
+
+    let (cached, force_cpu) = {
+        let inner = inner_arc.lock().unwrap();
+        (inner.profile_engine.clone(), inner.config.force_cpu)
+    };
+
+    if let Some((id, engine)) = cached {
+        if id == model_id {
+            return Ok(engine);
+        }
+    }
+
+    let path = models::get_model_path(app_data_dir, model_id).ok_or_else(|| {
+        AppError::Transcription(format!("Modèle du profil introuvable : {}", model_id))
+    })?;
+    let engine = load_model(&path, force_cpu)?;
+
+    log::info!("Moteur du profil chargé : {} ({})", engine.name(), model_id);
+    let mut inner = inner_arc.lock().unwrap();
+    inner.profile_engine = Some((model_id.to_string(), engine.clone()));
+    Ok(engine)
+}
+
+/// Run Whisper and collect the timed segments.
+///
+/// `on_progress` receives the overall completion percentage.
+///
+/// Inputs longer than 30 s are split into overlapping windows at silences
+/// (see `segmentation`) and stitched back into a single result.
+pub fn transcribe_segments<F>(
+    ctx: &WhisperContext,
+    audio: &[f32],
+    options: &TranscribeOptions,
+    on_progress: Option<F>,
+) -> AppResult<TranscriptionResult>
+where
+    F: FnMut(i32) + 'static,
+{
+    let duration_ms = audio.len() as i64 * 1000 / 16000;
+
     if audio.is_empty() {
-        return Ok(String::new());
+        return Ok(TranscriptionResult {
+            text: String::new(),
+            segments: Vec::new(),
+            language: options.language.clone(),
+            duration_ms,
+        });
     }
 
+    let windows = segmentation::plan_windows(audio);
+    if windows.len() == 1 {
+        return run_whisper(ctx, audio, options, on_progress);
+    }
+
+    log::info!(
+        "Audio long ({} ms) : découpage en {} fenêtres",
+        duration_ms,
+        windows.len()
+    );
+
+    // Each window reports 0-100; map that onto the window's share of the total
+    let on_progress = on_progress.map(|f| Rc::new(RefCell::new(f)));
+    let n_windows = windows.len() as i32;
+    let mut parts = Vec::with_capacity(windows.len());
+
+    for (i, window) in windows.into_iter().enumerate() {
+        let window_progress = on_progress.clone().map(|f| {
+            let base = i as i32 * 100 / n_windows;
+            move |percent: i32| (*f.borrow_mut())(base + percent / n_windows)
+        });
+        let part = run_whisper(ctx, &audio[window.start..window.end], options, window_progress)?;
+        parts.push((window, part));
+    }
+
+    Ok(segmentation::stitch(parts, duration_ms))
+}
+
+fn run_whisper<F>(
+    ctx: &WhisperContext,
+    audio: &[f32],
+    options: &TranscribeOptions,
+    on_progress: Option<F>,
+) -> AppResult<TranscriptionResult>
+where
+    F: FnMut(i32) + 'static,
+{
+    let duration_ms = audio.len() as i64 * 1000 / 16000;
+    let language = options.language.as_str();
+
     let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
     let lang = if language == "auto" { None } else { Some(language) };
     params.set_language(lang);
@@ -27,8 +281,10 @@ pub fn transcribe(ctx: &WhisperContext, audio: &[f32], language: &str) -> AppRes
     params.set_print_progress(false);
     params.set_print_realtime(false);
     params.set_print_timestamps(false);
-    params.set_no_timestamps(true);
+    params.set_no_timestamps(!options.timestamps);
     params.set_single_segment(false);
+    params.set_tdrz_enable(options.diarize);
+    params.set_progress_callback_safe::<_, F>(on_progress);
 
     let mut state = ctx
         .create_state()
@@ -43,12 +299,63 @@ pub fn transcribe(ctx: &WhisperContext, audio: &[f32], language: &str) -> AppRes
         .map_err(|e| AppError::Transcription(format!("Lecture segments impossible : {}", e)))?;
 
     let mut text = String::new();
+    let mut segments = Vec::with_capacity(n_segments.max(0) as usize);
+    // tinydiarize only marks turns, not identities: labels alternate 1 <-> 2
+    let mut speaker = 1;
     for i in 0..n_segments {
         let segment = state
             .full_get_segment_text(i)
             .map_err(|e| AppError::Transcription(format!("Lecture segment {} impossible : {}", i, e)))?;
         text.push_str(&segment);
+
+        // whisper.cpp reports timestamps in centiseconds
+        let t0 = state.full_get_segment_t0(i).unwrap_or(0);
+        let t1 = state.full_get_segment_t1(i).unwrap_or(t0);
+        segments.push(Segment {
+            start_ms: t0 * 10,
+            end_ms: t1 * 10,
+            text: segment.trim().to_string(),
+            confidence: segment_confidence(ctx, &state, i),
+            speaker: options.diarize.then_some(speaker),
+        });
+
+        if options.diarize && state.full_get_segment_speaker_turn_next(i) {
+            speaker = 3 - speaker;
+        }
     }
 
-    Ok(text.trim().to_string())
+    // Report the detected language so language-specific text stages can run
+    let language = if language == "auto" {
+        state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .unwrap_or("auto")
+    } else {
+        language
+    };
+
+    Ok(TranscriptionResult {
+        text: text.trim().to_string(),
+        segments,
+        language: language.to_string(),
+        duration_ms,
+    })
+}
+
+/// Average probability of the text tokens of a segment (special tokens excluded).
+fn segment_confidence(ctx: &WhisperContext, state: &WhisperState, segment: i32) -> f32 {
+    let eot = ctx.token_eot();
+    let n_tokens = state.full_n_tokens(segment).unwrap_or(0);
+
+    let (sum, count) = (0..n_tokens)
+        .filter(|&t| state.full_get_token_id(segment, t).map(|id| id < eot).unwrap_or(false))
+        .filter_map(|t| state.full_get_token_prob(segment, t).ok())
+        .fold((0.0f32, 0u32), |(sum, count), p| (sum + p, count + 1));
+
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
 }
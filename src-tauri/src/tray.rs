@@ -6,6 +6,7 @@ use tauri::tray::TrayIconBuilder;
 use tauri::{AppHandle, Manager};
 
 use crate::errors::{AppError, AppResult};
+use crate::undo;
 
 static PROCESSING_ACTIVE: AtomicBool = AtomicBool::new(false);
 
@@ -35,13 +36,16 @@ pub fn set_dock_visible(visible: bool) {
 }
 
 pub fn setup_tray(app: &AppHandle) -> AppResult<()> {
-    let settings_item = MenuItem::with_id(app, "settings", "Parametres", true, None::<&str>)
+    let settings_item = MenuItem::with_id(app, "settings", "Paramètres", true, None::<&str>)
+        .map_err(|e| AppError::Config(format!("Creation menu impossible : {}", e)))?;
+
+    let undo_item = MenuItem::with_id(app, "undo", "Annuler la dernière dictée", true, None::<&str>)
         .map_err(|e| AppError::Config(format!("Creation menu impossible : {}", e)))?;
 
     let quit_item = MenuItem::with_id(app, "quit", "Quitter", true, None::<&str>)
         .map_err(|e| AppError::Config(format!("Creation menu impossible : {}", e)))?;
 
-    let menu = Menu::with_items(app, &[&settings_item, &quit_item])
+    let menu = Menu::with_items(app, &[&settings_item, &undo_item, &quit_item])
         .map_err(|e| AppError::Config(format!("Creation menu impossible : {}", e)))?;
 
     let icon = Image::from_bytes(include_bytes!("../icons/tray-idle.png"))
@@ -62,6 +66,7 @@ pub fn setup_tray(app: &AppHandle) -> AppResult<()> {
                     let _ = win.set_focus();
                 }
             }
+            "undo" => undo::trigger(app),
             "quit" => app.exit(0),
             _ => {}
         })
@@ -127,3 +132,17 @@ pub fn start_processing_animation(app: &AppHandle) {
 pub fn stop_processing_animation() {
     PROCESSING_ACTIVE.store(false, Ordering::SeqCst);
 }
+
+/// Show the watch-folder queue length in the tray tooltip (0 resets it).
+pub fn set_watch_folder_status(app: &AppHandle, pending: usize) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+
+    let tooltip = if pending > 0 {
+        format!("LocalWhisper - Dossier surveillé : {} fichier(s) en attente", pending)
+    } else {
+        "LocalWhisper".to_string()
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+}
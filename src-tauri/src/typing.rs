@@ -0,0 +1,36 @@
+//! Type-out output: the dictation is entered as simulated keystrokes instead
+//! of going through the clipboard, for applications that block paste.
+
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::config::InputBackend;
+use crate::config::TypingConfig;
+use crate::errors::AppResult;
+use crate::injection::{self, Action};
+
+/// Type `text` into the focused window, `chunk_size` characters at a time so
+/// the main thread is never blocked for the whole text.
+pub fn type_text(
+    app: &AppHandle,
+    text: &str,
+    config: &TypingConfig,
+    backend: InputBackend,
+) -> AppResult<()> {
+    let char_delay = Duration::from_millis(config.char_delay_ms);
+    let chars: Vec<char> = text.chars().collect();
+
+    for chunk in chars.chunks(config.chunk_size.max(1)) {
+        let chunk: String = chunk.iter().collect();
+        injection::inject(
+            app,
+            backend,
+            &Action::Text {
+                text: &chunk,
+                char_delay,
+            },
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Undo of the last dictations: the inserted text is erased with as many
+//! backspaces as it has characters, provided the window it went to still has
+//! the focus. Where the focused window cannot be detected (Wayland, macOS,
+//! Windows), undo is refused rather than erasing text blindly.
+
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::active_window;
+use crate::chord::{Chord, ChordKey};
+use crate::errors::{AppError, AppResult};
+use crate::injection::{self, Action};
+use crate::state::AppState;
+
+/// Erase the most recent insertion still on the stack. Blocks while the keys
+/// are sent, so it must not run on the main thread.
+pub fn undo_last(app: &AppHandle) -> AppResult<String> {
+    let state: tauri::State<AppState> = app.state();
+    let (insertion, backend) = {
+        let inner = state.inner.lock().unwrap();
+        let insertion = inner
+            .insertions
+            .last()
+            .cloned()
+            .ok_or_else(|| AppError::Undo("aucune dictée à annuler".to_string()))?;
+        (insertion, inner.config.input_backend)
+    };
+
+    let Some(focused) = active_window::detect().map(|w| w.id) else {
+        return Err(AppError::Undo(
+            "fenêtre active indétectable sur cette session".to_string(),
+        ));
+    };
+    if insertion.window != Some(focused) {
+        return Err(AppError::Undo(
+            "la fenêtre de la dictée n'a plus le focus".to_string(),
+        ));
+    }
+
+    let backspace = Chord {
+        modifiers: Vec::new(),
+        key: ChordKey::Backspace,
+    };
+    // One backspace erases one user-perceived character: "é" written as
+    // e + combining accent, or an emoji with modifiers, goes at once
+    let action = Action::Chord {
+        chord: &backspace,
+        times: insertion.text.graphemes(true).count(),
+    };
+    let sent = injection::inject(app, backend, &action);
+
+    let mut inner = state.inner.lock().unwrap();
+    match sent {
+        Ok(()) => {
+            inner.insertions.pop();
+            Ok(insertion.text)
+        }
+        Err(e) => {
+            // Part of the text may be gone, so the stack no longer matches
+            inner.insertions.clear();
+            Err(e)
+        }
+    }
+}
+
+/// Run `undo_last` off the calling thread and report the outcome, for the
+/// hotkey and the tray item.
+pub fn trigger(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        // Let the hotkey modifiers go first, or Backspace would be sent as
+        // Ctrl+Backspace and erase whole words
+        std::thread::sleep(Duration::from_millis(300));
+
+        match undo_last(&app) {
+            Ok(text) => {
+                log::info!("Dictée annulée : {}", text);
+                let _ = app.emit("dictation-undone", text);
+            }
+            Err(e) => {
+                log::warn!("{}", e);
+                let _ = app.emit("error", e.to_string());
+            }
+        }
+    });
+}
@@ -0,0 +1,345 @@
+//! Spoken punctuation and formatting: "comma", "new line", "à la ligne"...
+//! are replaced by the characters they stand for. Each language has its own
+//! grammar, plus an escape word to dictate a command word literally
+//! ("literal comma" -> "comma", "littéralement virgule" -> "virgule").
+//! User-added commands can be marked explicit to apply only after the
+//! grammar's trigger word ("symbol tab").
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::errors::{AppError, AppResult};
+use crate::postprocess::capitalize_first;
+
+const GRAMMAR_FILE: &str = "voice_commands.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCommand {
+    /// Spoken form, matched case- and punctuation-insensitively
+    pub phrase: String,
+    /// Text inserted in place of the phrase
+    pub output: String,
+    #[serde(default)]
+    pub attach: Attach,
+    /// Only applies after the trigger word, for phrases common in prose
+    #[serde(default)]
+    pub explicit: bool,
+}
+
+/// Which neighbouring words the output sticks to (no space in between).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Attach {
+    #[default]
+    None,
+    Previous,
+    Next,
+    Both,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Grammar {
+    /// Word that makes the following command phrase literal
+    pub escape: String,
+    /// Word that makes the following phrase a command, explicit ones included
+    #[serde(default)]
+    pub trigger: String,
+    pub commands: Vec<VoiceCommand>,
+}
+
+/// Grammars keyed by language code.
+pub type Grammars = HashMap<String, Grammar>;
+
+fn command(phrase: &str, output: &str, attach: Attach) -> VoiceCommand {
+    VoiceCommand {
+        phrase: phrase.to_string(),
+        output: output.to_string(),
+        attach,
+        explicit: false,
+    }
+}
+
+fn default_grammars() -> Grammars {
+    use Attach::*;
+
+    let en = Grammar {
+        escape: "literal".to_string(),
+        trigger: "symbol".to_string(),
+        commands: vec![
+            command("new paragraph", "\n\n", Both),
+            command("new line", "\n", Both),
+            command("comma", ",", Previous),
+            command("period", ".", Previous),
+            command("full stop", ".", Previous),
+            command("question mark", "?", Previous),
+            command("exclamation mark", "!", Previous),
+            command("exclamation point", "!", Previous),
+            command("colon", ":", Previous),
+            command("semicolon", ";", Previous),
+            command("open quote", "\"", Next),
+            command("close quote", "\"", Previous),
+            command("open parenthesis", "(", Next),
+            command("close parenthesis", ")", Previous),
+            command("hyphen", "-", Both),
+        ],
+    };
+
+    // French typography keeps a space before ; : ! ? and inside « »
+    let fr = Grammar {
+        escape: "littéralement".to_string(),
+        trigger: "symbole".to_string(),
+        commands: vec![
+            command("nouveau paragraphe", "\n\n", Both),
+            command("à la ligne", "\n", Both),
+            command("nouvelle ligne", "\n", Both),
+            command("point à la ligne", ".\n", Both),
+            command("virgule", ",", Previous),
+            command("point final", ".", Previous),
+            command("point d'interrogation", "?", None),
+            command("point d'exclamation", "!", None),
+            command("deux points", ":", None),
+            command("point-virgule", ";", None),
+            command("ouvrez les guillemets", "«", None),
+            command("fermez les guillemets", "»", None),
+            command("ouvrez la parenthèse", "(", Next),
+            command("fermez la parenthèse", ")", Previous),
+            command("trait d'union", "-", Both),
+        ],
+    };
+
+    HashMap::from([("en".to_string(), en), ("fr".to_string(), fr)])
+}
+
+/// Load the grammars from `app_data_dir`, creating the file with the
+/// built-in defaults on first use.
+pub fn load_grammars(app_data_dir: &Path) -> AppResult<Grammars> {
+    let path = app_data_dir.join(GRAMMAR_FILE);
+
+    if !path.exists() {
+        let grammars = default_grammars();
+        save_grammars(app_data_dir, &grammars)?;
+        return Ok(grammars);
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| AppError::Config(format!("Lecture impossible : {}", e)))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Config(format!("Parsing impossible : {}", e)))
+}
+
+pub fn save_grammars(app_data_dir: &Path, grammars: &Grammars) -> AppResult<()> {
+    std::fs::create_dir_all(app_data_dir)?;
+    let json = serde_json::to_string_pretty(grammars)
+        .map_err(|e| AppError::Config(format!("Sérialisation impossible : {}", e)))?;
+    std::fs::write(app_data_dir.join(GRAMMAR_FILE), json)?;
+    Ok(())
+}
+
+/// A word of the transcript with the separator that preceded it
+/// (a space, or "-" inside hyphenated words).
+pub struct Word<'a> {
+    pub text: &'a str,
+    pub key: String,
+    pub separator: &'a str,
+    /// Byte offset of `text` in the transcript
+    pub start: usize,
+}
+
+/// Lowercase, without surrounding punctuation, typographic apostrophes folded.
+fn word_key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .replace('’', "'")
+        .to_lowercase()
+}
+
+pub fn split_words(text: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    for (i, token) in text.split_whitespace().enumerate() {
+        for (j, part) in token.split('-').enumerate() {
+            if part.is_empty() {
+                continue;
+            }
+            let separator = match (i, j) {
+                (0, 0) => "",
+                (_, 0) => " ",
+                _ => "-",
+            };
+            words.push(Word {
+                text: part,
+                key: word_key(part),
+                separator,
+                // `part` borrows from `text`
+                start: part.as_ptr() as usize - text.as_ptr() as usize,
+            });
+        }
+    }
+    words
+}
+
+pub fn phrase_keys(phrase: &str) -> Vec<String> {
+    phrase
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .map(word_key)
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+/// Length in bytes of the punctuation Whisper put at the end of `text`.
+fn trailing_punctuation_len(text: &str) -> usize {
+    let trimmed = text.trim_end_matches([',', '.', ';', ':', '!', '?']);
+    text.len() - trimmed.len()
+}
+
+/// Append a transcript word, returning the length of its trailing punctuation.
+fn push_word(out: &mut String, word: &Word, glue: &mut bool, capitalize: &mut bool) -> usize {
+    if !out.is_empty() && !*glue {
+        out.push_str(word.separator);
+    }
+    if *capitalize {
+        out.push_str(&capitalize_first(word.text));
+    } else {
+        out.push_str(word.text);
+    }
+    *glue = false;
+    *capitalize = false;
+    trailing_punctuation_len(word.text)
+}
+
+/// Replace the command phrases of `grammar` found in `text`.
+pub fn apply_grammar(text: &str, grammar: &Grammar) -> String {
+    let words = split_words(text);
+    let escape = word_key(&grammar.escape);
+    let trigger = word_key(&grammar.trigger);
+
+    // Longest phrases first so "point à la ligne" wins over "à la ligne"
+    let mut commands: Vec<(Vec<String>, &VoiceCommand)> = grammar
+        .commands
+        .iter()
+        .map(|c| (phrase_keys(&c.phrase), c))
+        .filter(|(keys, _)| !keys.is_empty())
+        .collect();
+    commands.sort_by_key(|(keys, _)| std::cmp::Reverse(keys.len()));
+
+    // `triggered` lets explicit commands match too
+    let match_at = |i: usize, triggered: bool| {
+        commands.iter().find(|(keys, cmd)| {
+            (triggered || !cmd.explicit)
+                && i + keys.len() <= words.len()
+                && keys.iter().zip(&words[i..]).all(|(k, w)| *k == w.key)
+        })
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut glue_next = false;
+    let mut capitalize_next = false;
+    // Punctuation ending the last word pushed, dropped if a command follows
+    let mut word_tail = 0;
+    let mut i = 0;
+
+    while i < words.len() {
+        if !escape.is_empty() && words[i].key == escape {
+            if let Some((keys, _)) = match_at(i + 1, true) {
+                for word in &words[i + 1..i + 1 + keys.len()] {
+                    word_tail = push_word(&mut out, word, &mut glue_next, &mut capitalize_next);
+                }
+                i += 1 + keys.len();
+                continue;
+            }
+        }
+
+        // The trigger word itself is dropped along with the phrase
+        let triggered = !trigger.is_empty() && words[i].key == trigger;
+        let found = match triggered.then(|| match_at(i + 1, true)).flatten() {
+            Some(found) => {
+                i += 1;
+                Some(found)
+            }
+            None => match_at(i, false),
+        };
+        let Some((keys, cmd)) = found else {
+            word_tail = push_word(&mut out, &words[i], &mut glue_next, &mut capitalize_next);
+            i += 1;
+            continue;
+        };
+
+        out.truncate(out.len() - word_tail);
+        word_tail = 0;
+        if matches!(cmd.attach, Attach::Previous | Attach::Both) {
+            out.truncate(out.trim_end_matches(' ').len());
+        } else if !out.is_empty() && !glue_next && !out.ends_with('\n') {
+            out.push(' ');
+        }
+        out.push_str(&cmd.output);
+
+        glue_next = matches!(cmd.attach, Attach::Next | Attach::Both);
+        capitalize_next = cmd
+            .output
+            .trim_end_matches(' ')
+            .ends_with(['.', '!', '?', '\n']);
+        i += keys.len();
+    }
+
+    out
+}
+
+/// Apply the grammar for `language`, if there is one.
+pub fn process(text: &str, language: &str, grammars: &Grammars) -> String {
+    match grammars.get(language) {
+        Some(grammar) => apply_grammar(text, grammar),
+        None => text.to_string(),
+    }
+}
+
+/// Load the grammars from `app_data_dir` and process `text`.
+pub fn apply(text: &str, language: &str, app_data_dir: &Path) -> String {
+    match load_grammars(app_data_dir) {
+        Ok(grammars) => process(text, language, &grammars),
+        Err(e) => {
+            log::error!("Commandes vocales illisibles : {}", e);
+            process(text, language, &default_grammars())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, language: &str) -> String {
+        process(text, language, &default_grammars())
+    }
+
+    #[test]
+    fn spoken_punctuation_is_replaced() {
+        assert_eq!(run("Hello comma world period", "en"), "Hello, world.");
+        assert_eq!(run("Bonjour virgule ça va point d'interrogation", "fr"), "Bonjour, ça va ?");
+        assert_eq!(run("Voici deux points la liste", "fr"), "Voici : la liste");
+    }
+
+    #[test]
+    fn line_breaks_capitalize_the_next_word() {
+        assert_eq!(run("First item new line second item", "en"), "First item\nSecond item");
+        assert_eq!(run("Fin point à la ligne suite", "fr"), "Fin.\nSuite");
+    }
+
+    #[test]
+    fn escape_word_keeps_the_phrase() {
+        assert_eq!(run("Add a literal comma here", "en"), "Add a comma here");
+        assert_eq!(run("Type literal new line here", "en"), "Type new line here");
+        assert_eq!(run("Il reste littéralement deux points", "fr"), "Il reste deux points");
+    }
+
+    #[test]
+    fn trigger_word_applies_explicit_commands() {
+        let mut grammars = default_grammars();
+        let en = grammars.get_mut("en").unwrap();
+        en.commands.push(VoiceCommand {
+            explicit: true,
+            ..command("tab", "\t", Attach::Both)
+        });
+        assert_eq!(process("Press tab twice", "en", &grammars), "Press tab twice");
+        assert_eq!(process("Name symbol tab value", "en", &grammars), "Name\tvalue");
+    }
+}
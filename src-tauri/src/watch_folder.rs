@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio_file;
+use crate::config::WatchFolderConfig;
+use crate::errors::{AppError, AppResult};
+use crate::export;
+use crate::file_transcription;
+use crate::state::AppState;
+use crate::transcription;
+use crate::tray;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+const DONE_DIR: &str = "done";
+const LEDGER_FILE: &str = "watch_ledger.json";
+/// A failed file is retried this many times before it is left alone, unless
+/// it changes on disk.
+const MAX_ATTEMPTS: u32 = 3;
+/// Wait between two attempts on a failed file, multiplied by the attempts
+/// already made.
+const RETRY_DELAY_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchFolderProgress {
+    pub path: String,
+    /// "queued", "processing", "done" or "failed"
+    pub status: String,
+    pub pending: usize,
+    pub error: Option<String>,
+}
+
+/// Record of a file already transcribed, so it is never transcribed twice —
+/// even across restarts, and even when files are left in place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LedgerEntry {
+    size: u64,
+    modified: u64,
+    processed_at: u64,
+    transcript: String,
+}
+
+/// Record of a file whose transcription failed, kept until it succeeds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FailedEntry {
+    size: u64,
+    modified: u64,
+    failed_at: u64,
+    attempts: u32,
+    error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Ledger {
+    files: HashMap<String, LedgerEntry>,
+    #[serde(default)]
+    failed: HashMap<String, FailedEntry>,
+}
+
+impl Ledger {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join(LEDGER_FILE)
+    }
+
+    fn load(app_data_dir: &Path) -> Self {
+        std::fs::read_to_string(Self::path(app_data_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, app_data_dir: &Path) -> AppResult<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::Config(format!("Sérialisation impossible : {}", e)))?;
+        std::fs::write(Self::path(app_data_dir), json)?;
+        Ok(())
+    }
+
+    fn contains(&self, path: &Path, size: u64, modified: u64) -> bool {
+        self.files
+            .get(&path.display().to_string())
+            .map(|e| e.size == size && e.modified == modified)
+            .unwrap_or(false)
+    }
+
+    /// Attempts already made on this version of the file.
+    fn attempts(&self, path: &Path, size: u64, modified: u64) -> u32 {
+        self.failed
+            .get(&path.display().to_string())
+            .filter(|e| e.size == size && e.modified == modified)
+            .map(|e| e.attempts)
+            .unwrap_or(0)
+    }
+
+    /// Whether a file that failed before should wait: it is given up after
+    /// `MAX_ATTEMPTS`, and a modified file starts over.
+    fn backing_off(&self, path: &Path, size: u64, modified: u64, now: u64) -> bool {
+        self.failed
+            .get(&path.display().to_string())
+            .filter(|e| e.size == size && e.modified == modified)
+            .map(|e| {
+                e.attempts >= MAX_ATTEMPTS
+                    || now < e.failed_at + RETRY_DELAY_SECS * u64::from(e.attempts)
+            })
+            .unwrap_or(false)
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Audio files in `dir` (non-recursive) with their size and modification time.
+fn scan(dir: &Path) -> Vec<(PathBuf, u64, u64)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && audio_file::is_supported(p))
+        .filter_map(|p| {
+            let meta = std::fs::metadata(&p).ok()?;
+            let modified = meta.modified().map(unix_secs).unwrap_or(0);
+            Some((p, meta.len(), modified))
+        })
+        .collect();
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+fn emit_progress(app: &AppHandle, path: &Path, status: &str, pending: usize, error: Option<String>) {
+    let _ = app.emit(
+        "watch-folder-progress",
+        WatchFolderProgress {
+            path: path.display().to_string(),
+            status: status.to_string(),
+            pending,
+            error,
+        },
+    );
+}
+
+/// Start the background poller. It runs for the whole app lifetime and
+/// re-reads the config on every tick, so enabling, disabling or changing the
+/// folder takes effect without a restart.
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        let app_data_dir = {
+            let state: tauri::State<AppState> = handle.state();
+            let inner = state.inner.lock().unwrap();
+            inner.app_data_dir.clone()
+        };
+        let mut ledger = Ledger::load(&app_data_dir);
+        // Sizes seen on the previous tick: a file is only picked up once its
+        // size is stable, so we never read a half-copied recording.
+        let mut last_sizes: HashMap<PathBuf, u64> = HashMap::new();
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let config = {
+                let state: tauri::State<AppState> = handle.state();
+                let inner = state.inner.lock().unwrap();
+                inner.config.watch_folder.clone()
+            };
+
+            let dir = match (config.enabled, config.path.as_deref()) {
+                (true, Some(path)) if !path.is_empty() => PathBuf::from(path),
+                _ => {
+                    last_sizes.clear();
+                    continue;
+                }
+            };
+
+            let files = scan(&dir);
+            let now = unix_secs(SystemTime::now());
+            let mut queue = Vec::new();
+            let mut current_sizes = HashMap::new();
+            for (path, size, modified) in files {
+                if ledger.contains(&path, size, modified)
+                    || ledger.backing_off(&path, size, modified, now)
+                {
+                    continue;
+                }
+                if last_sizes.get(&path) == Some(&size) {
+                    queue.push((path.clone(), size, modified));
+                }
+                current_sizes.insert(path, size);
+            }
+            last_sizes = current_sizes;
+
+            if queue.is_empty() {
+                continue;
+            }
+
+            // Without a usable model, leave the files queued for a later tick
+            // instead of recording them as failed.
+            let inner_arc = {
+                let state: tauri::State<AppState> = handle.state();
+                state.inner.clone()
+            };
+            if let Err(e) = transcription::ensure_model(&inner_arc, &app_data_dir) {
+                log::warn!("Dossier surveillé en attente d'un modèle : {}", e);
+                continue;
+            }
+
+            for (path, _, _) in &queue {
+                emit_progress(&handle, path, "queued", queue.len(), None);
+            }
+
+            let total = queue.len();
+            for (i, (path, size, modified)) in queue.into_iter().enumerate() {
+                let pending = total - i;
+                tray::set_watch_folder_status(&handle, pending);
+                emit_progress(&handle, &path, "processing", pending, None);
+
+                let key = path.display().to_string();
+                let processed_at = unix_secs(SystemTime::now());
+                match process_file(&handle, &dir, &path, &config) {
+                    Ok(transcript) => {
+                        emit_progress(&handle, &path, "done", pending - 1, None);
+                        ledger.failed.remove(&key);
+                        ledger.files.insert(
+                            key,
+                            LedgerEntry {
+                                size,
+                                modified,
+                                processed_at,
+                                transcript,
+                            },
+                        );
+                    }
+                    Err(e) => {
+                        let attempts = ledger.attempts(&path, size, modified) + 1;
+                        log::error!(
+                            "Dossier surveillé : échec pour {} (essai {}/{}) : {}",
+                            path.display(),
+                            attempts,
+                            MAX_ATTEMPTS,
+                            e
+                        );
+                        emit_progress(&handle, &path, "failed", pending - 1, Some(e.clone()));
+                        let _ = handle.emit(
+                            "error",
+                            format!("Transcription de {} impossible : {}", path.display(), e),
+                        );
+                        ledger.failed.insert(
+                            key,
+                            FailedEntry {
+                                size,
+                                modified,
+                                failed_at: processed_at,
+                                attempts,
+                                error: e,
+                            },
+                        );
+                    }
+                }
+                last_sizes.remove(&path);
+                if let Err(e) = ledger.save(&app_data_dir) {
+                    log::error!("Sauvegarde du registre impossible : {}", e);
+                }
+            }
+
+            tray::set_watch_folder_status(&handle, 0);
+        }
+    });
+}
+
+/// First name in `dir` for which neither `{name}.{ext}` exists for any of
+/// `extensions`: `name`, then `name (2)`, `name (3)`...
+fn free_name(dir: &Path, name: &str, extensions: &[&str]) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => name.to_string(),
+            n => format!("{} ({})", name, n),
+        })
+        .find(|candidate| {
+            extensions
+                .iter()
+                .all(|ext| !dir.join(format!("{}.{}", candidate, ext)).exists())
+        })
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Transcribe one file and write its sidecar transcript, returning the
+/// transcript path. With `move_to_done`, the audio then joins the transcript
+/// in `done/`. Either way nothing is overwritten: names already taken get a
+/// " (2)", " (3)"... suffix.
+fn process_file(
+    app: &AppHandle,
+    dir: &Path,
+    path: &Path,
+    config: &WatchFolderConfig,
+) -> Result<String, String> {
+    let inner_arc = {
+        let state: tauri::State<AppState> = app.state();
+        state.inner.clone()
+    };
+
+    let result =
+        file_transcription::transcribe_file(app, &inner_arc, path).map_err(|e| e.to_string())?;
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let audio_ext = path.extension().unwrap_or_default().to_string_lossy().to_string();
+    let output_ext = config.output_format.extension();
+
+    let done = dir.join(DONE_DIR);
+    let target_dir = if config.move_to_done {
+        match std::fs::create_dir_all(&done) {
+            Ok(()) => Some(done),
+            Err(e) => {
+                log::warn!("Création de {} impossible : {}", done.display(), e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let Some(target_dir) = target_dir else {
+        // Left in place, the transcript must not overwrite a file of the
+        // user's, nor the transcript of another recording with the same stem
+        let name = free_name(dir, &stem, &[output_ext]);
+        let sidecar = dir.join(format!("{}.{}", name, output_ext));
+        export::write(&result, config.output_format, &sidecar).map_err(|e| e.to_string())?;
+        return Ok(sidecar.display().to_string());
+    };
+
+    // Written before the audio moves, so a failure leaves the audio in place
+    // for the next attempt
+    let name = free_name(&target_dir, &stem, &[&audio_ext, output_ext]);
+    let sidecar = target_dir.join(format!("{}.{}", name, output_ext));
+    export::write(&result, config.output_format, &sidecar).map_err(|e| e.to_string())?;
+
+    let moved = target_dir.join(format!("{}.{}", name, audio_ext));
+    if let Err(e) = std::fs::rename(path, &moved) {
+        log::warn!("Déplacement de {} impossible : {}", path.display(), e);
+    }
+    Ok(sidecar.display().to_string())
+}
@@ -3,6 +3,7 @@ import { getSystemInfo } from "../lib/commands";
 import { useModels } from "../hooks/useModels";
 import { useI18n } from "../lib/i18n";
 import { ModelCard } from "./ModelCard";
+import { RecommendationReasons } from "./RecommendationReasons";
 import type { SystemInfo } from "../lib/types";
 
 export function ModelCatalog() {
@@ -29,9 +30,7 @@ export function ModelCatalog() {
               cores: systemInfo.cpu_cores,
             })}
           </p>
-          <p className="recommendation">
-            {systemInfo.recommended_model_reason}
-          </p>
+          <RecommendationReasons reasons={systemInfo.recommendation_reasons} />
         </div>
       )}
 
@@ -0,0 +1,61 @@
+import { useI18n } from "../lib/i18n";
+import type { RecommendationReason } from "../lib/types";
+
+interface Props {
+  reasons: RecommendationReason[];
+}
+
+export function RecommendationReasons({ reasons }: Props) {
+  const { t } = useI18n();
+
+  const speed = (rtf: number) => (1 / Math.max(rtf, 0.01)).toFixed(1);
+
+  const describe = (reason: RecommendationReason) => {
+    switch (reason.kind) {
+      case "benchmark":
+        return t("recommendation.benchmark", {
+          speed: speed(reason.real_time_factor),
+        });
+      case "benchmark_too_slow":
+        return t("recommendation.benchmarkTooSlow", {
+          speed: speed(reason.real_time_factor),
+        });
+      case "available_memory":
+        return t("recommendation.availableMemory", {
+          available: reason.available_gb,
+          total: reason.total_gb,
+        });
+      case "cpu":
+        return t("recommendation.cpu", {
+          cores: reason.cores,
+          simd:
+            reason.simd.length > 0
+              ? reason.simd.join(", ").toUpperCase()
+              : t("recommendation.noSimd"),
+        });
+      case "gpu":
+        return t("recommendation.gpu", {
+          backend: reason.backend,
+          device: reason.device,
+        });
+      case "no_gpu":
+        return t("recommendation.noGpu", { backend: reason.backend });
+      case "limited_by_memory":
+        return t("recommendation.limitedByMemory");
+      case "limited_by_compute":
+        return t("recommendation.limitedByCompute");
+      case "quantized":
+        return t("recommendation.quantized");
+      case "english_only":
+        return t("recommendation.englishOnly");
+    }
+  };
+
+  return (
+    <ul className="recommendation">
+      {reasons.map((reason, i) => (
+        <li key={i}>{describe(reason)}</li>
+      ))}
+    </ul>
+  );
+}
@@ -8,6 +8,7 @@ import {
 import { useModels } from "../hooks/useModels";
 import { useI18n } from "../lib/i18n";
 import { DownloadProgress } from "./DownloadProgress";
+import { RecommendationReasons } from "./RecommendationReasons";
 import type { SystemInfo } from "../lib/types";
 
 interface Props {
@@ -49,8 +50,11 @@ export function SetupWizard({ onComplete }: Props) {
     }
   };
 
+  // The recommendation may be an English-only or quantized variant
   const mainModels = models.filter(
-    (m) => !m.is_quantized && !m.is_english_only,
+    (m) =>
+      (!m.is_quantized && !m.is_english_only) ||
+      m.id === systemInfo?.recommended_model,
   );
 
   return (
@@ -78,9 +82,9 @@ export function SetupWizard({ onComplete }: Props) {
           <div className="wizard-content">
             <h2>{t("wizard.chooseModel")}</h2>
             {systemInfo && (
-              <p className="recommendation">
-                {systemInfo.recommended_model_reason}
-              </p>
+              <RecommendationReasons
+                reasons={systemInfo.recommendation_reasons}
+              />
             )}
 
             <div className="model-selection">
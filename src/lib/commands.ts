@@ -1,5 +1,33 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { AppConfig, AudioDevice, ModelInfo, PermissionStatus, SystemInfo } from "./types";
+import type {
+  ActiveWindow,
+  AppConfig,
+  AppProfile,
+  AudioDevice,
+  BenchmarkResult,
+  ClipboardRestoreConfig,
+  ContinuationConfig,
+  ExportFormat,
+  HookConfig,
+  InjectionStatus,
+  InputBackend,
+  ModelInfo,
+  OutputMode,
+  OutputSink,
+  NormalizationConfig,
+  PermissionStatus,
+  PhantomPhrases,
+  RedactionConfig,
+  RewriteConfig,
+  Snippet,
+  SystemInfo,
+  TextRule,
+  TextRuleSets,
+  TranscriptionResult,
+  TypingConfig,
+  VoiceCommandGrammars,
+  WatchFolderConfig,
+} from "./types";
 
 export const getConfig = () => invoke<AppConfig>("get_config");
 
@@ -34,6 +62,15 @@ export const resumeHotkey = () => invoke("resume_hotkey");
 export const updateHotkeyPtt = (newHotkey: string): Promise<void> =>
   invoke("update_hotkey_ptt", { newHotkey });
 
+export const updateHotkeyUndo = (newHotkey: string): Promise<void> =>
+  invoke("update_hotkey_undo", { newHotkey });
+
+export const setUndoDepth = (depth: number) =>
+  invoke("set_undo_depth", { depth });
+
+/** Resolves with the erased text. */
+export const undoLastDictation = () => invoke<string>("undo_last_dictation");
+
 export const setAutoPaste = (enabled: boolean) =>
   invoke("set_auto_paste", { enabled });
 
@@ -46,6 +83,91 @@ export const setUiLocale = (locale: string) =>
 export const setAudioDevice = (deviceName: string | null) =>
   invoke("set_audio_device", { deviceName });
 
+export const setForceCpu = (enabled: boolean) =>
+  invoke("set_force_cpu", { enabled });
+
+export const setWatchFolder = (watchFolder: WatchFolderConfig) =>
+  invoke("set_watch_folder", { watchFolder });
+
+export const getPhantomPhrases = () =>
+  invoke<PhantomPhrases>("get_phantom_phrases");
+
+export const savePhantomPhrases = (phrases: PhantomPhrases) =>
+  invoke("save_phantom_phrases", { phrases });
+
+export const setVoiceCommands = (enabled: boolean) =>
+  invoke("set_voice_commands", { enabled });
+
+export const setNormalization = (normalization: NormalizationConfig) =>
+  invoke("set_normalization", { normalization });
+
+export const setRedaction = (redaction: RedactionConfig) =>
+  invoke("set_redaction", { redaction });
+
+export const setRewrite = (rewrite: RewriteConfig) =>
+  invoke("set_rewrite", { rewrite });
+
+export const testRewrite = (rewrite: RewriteConfig, text: string) =>
+  invoke<string>("test_rewrite", { rewrite, text });
+
+export const setHook = (hook: HookConfig) => invoke("set_hook", { hook });
+
+export const testHook = (hook: HookConfig, text: string) =>
+  invoke<string>("test_hook", { hook, text });
+
+export const setPasteChord = (chord: string) =>
+  invoke("set_paste_chord", { chord });
+
+export const setClipboardRestore = (restore: ClipboardRestoreConfig) =>
+  invoke("set_clipboard_restore", { restore });
+
+export const setOutputMode = (mode: OutputMode, typing: TypingConfig) =>
+  invoke("set_output_mode", { mode, typing });
+
+export const setInputBackend = (backend: InputBackend) =>
+  invoke("set_input_backend", { backend });
+
+export const getInjectionStatus = () =>
+  invoke<InjectionStatus>("get_injection_status");
+
+export const setContinuation = (continuation: ContinuationConfig) =>
+  invoke("set_continuation", { continuation });
+
+export const setSinks = (sinks: OutputSink[]) => invoke("set_sinks", { sinks });
+
+export const testSink = (sink: OutputSink, text: string) =>
+  invoke("test_sink", { sink, text });
+
+export const setProfiles = (profiles: AppProfile[]) =>
+  invoke("set_profiles", { profiles });
+
+export const getLastActiveWindow = () =>
+  invoke<ActiveWindow | null>("get_last_active_window");
+
+export const getVoiceCommands = () =>
+  invoke<VoiceCommandGrammars>("get_voice_commands");
+
+export const saveVoiceCommands = (grammars: VoiceCommandGrammars) =>
+  invoke("save_voice_commands", { grammars });
+
+export const listSnippets = () => invoke<Snippet[]>("list_snippets");
+
+export const addSnippet = (trigger: string, text: string) =>
+  invoke<Snippet>("add_snippet", { trigger, text });
+
+export const updateSnippet = (snippet: Snippet) =>
+  invoke("update_snippet", { snippet });
+
+export const deleteSnippet = (id: string) => invoke("delete_snippet", { id });
+
+export const getTextRules = () => invoke<TextRuleSets>("get_text_rules");
+
+export const saveTextRules = (rules: TextRuleSets) =>
+  invoke("save_text_rules", { rules });
+
+export const testTextRules = (rules: TextRule[], text: string) =>
+  invoke<string>("test_text_rules", { rules, text });
+
 export const testMicrophone = () => invoke("test_microphone");
 
 export const markSetupComplete = () => invoke("mark_setup_complete");
@@ -53,6 +175,23 @@ export const markSetupComplete = () => invoke("mark_setup_complete");
 export const getRecordingState = () =>
   invoke<boolean>("get_recording_state");
 
+export const transcribeFile = (path: string, outputPath?: string) =>
+  invoke<TranscriptionResult>("transcribe_file", {
+    path,
+    outputPath: outputPath ?? null,
+  });
+
+export const exportTranscript = (
+  result: TranscriptionResult,
+  format: ExportFormat,
+  path: string,
+) => invoke("export_transcript", { result, format, path });
+
+export const benchmarkModels = () =>
+  invoke<BenchmarkResult[]>("benchmark_models");
+
+export const getBenchmarks = () => invoke<BenchmarkResult[]>("get_benchmarks");
+
 export const checkPermissions = () =>
   invoke<PermissionStatus>("check_permissions");
 
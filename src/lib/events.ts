@@ -1,5 +1,12 @@
 import { listen } from "@tauri-apps/api/event";
-import type { DownloadProgress } from "./types";
+import type {
+  BenchmarkProgress,
+  DownloadProgress,
+  FileTranscriptionProgress,
+  RedactionWarning,
+  SinkError,
+  WatchFolderProgress,
+} from "./types";
 
 export const onRecordingStateChanged = (
   callback: (recording: boolean) => void,
@@ -23,3 +30,33 @@ export const onError = (callback: (error: string) => void) =>
 
 export const onMicTestLevel = (callback: (level: number) => void) =>
   listen<number>("mic-test-level", (e) => callback(e.payload));
+
+export const onFileTranscriptionProgress = (
+  callback: (progress: FileTranscriptionProgress) => void,
+) =>
+  listen<FileTranscriptionProgress>("file-transcription-progress", (e) =>
+    callback(e.payload),
+  );
+
+export const onWatchFolderProgress = (
+  callback: (progress: WatchFolderProgress) => void,
+) =>
+  listen<WatchFolderProgress>("watch-folder-progress", (e) =>
+    callback(e.payload),
+  );
+
+export const onBenchmarkProgress = (
+  callback: (progress: BenchmarkProgress) => void,
+) =>
+  listen<BenchmarkProgress>("benchmark-progress", (e) => callback(e.payload));
+
+export const onRedactionWarning = (
+  callback: (warning: RedactionWarning) => void,
+) =>
+  listen<RedactionWarning>("redaction-warning", (e) => callback(e.payload));
+
+export const onSinkError = (callback: (error: SinkError) => void) =>
+  listen<SinkError>("sink-error", (e) => callback(e.payload));
+
+export const onDictationUndone = (callback: (text: string) => void) =>
+  listen<string>("dictation-undone", (e) => callback(e.payload));
@@ -142,6 +142,49 @@ const translations = {
     fr: "Modeles disponibles",
   },
 
+  // Recommendation reasons
+  "recommendation.benchmark": {
+    en: "Measured on this machine: {speed}x faster than real time",
+    fr: "Mesure sur cette machine : {speed}x plus rapide que le temps reel",
+  },
+  "recommendation.benchmarkTooSlow": {
+    en: "No measured model is fast enough for dictation, the fastest one ({speed}x real time) is suggested",
+    fr: "Aucun modele mesure n'est assez rapide pour la dictee, le plus rapide ({speed}x le temps reel) est propose",
+  },
+  "recommendation.availableMemory": {
+    en: "{available} GB of memory free out of {total} GB",
+    fr: "{available} Go de memoire libre sur {total} Go",
+  },
+  "recommendation.cpu": {
+    en: "{cores} CPU cores, SIMD: {simd}",
+    fr: "{cores} coeurs CPU, SIMD : {simd}",
+  },
+  "recommendation.noSimd": { en: "none", fr: "aucun" },
+  "recommendation.gpu": {
+    en: "GPU acceleration ({backend}): {device}",
+    fr: "Acceleration GPU ({backend}) : {device}",
+  },
+  "recommendation.noGpu": {
+    en: "No usable GPU, transcription runs on the CPU ({backend} build)",
+    fr: "Aucun GPU utilisable, la transcription tourne sur le CPU (build {backend})",
+  },
+  "recommendation.limitedByMemory": {
+    en: "Model size limited by free memory",
+    fr: "Taille du modele limitee par la memoire libre",
+  },
+  "recommendation.limitedByCompute": {
+    en: "Model size limited by processing speed",
+    fr: "Taille du modele limitee par la vitesse de calcul",
+  },
+  "recommendation.quantized": {
+    en: "Quantized variant: the full-precision model does not fit in free memory",
+    fr: "Variante quantisee : le modele pleine precision ne tient pas en memoire libre",
+  },
+  "recommendation.englishOnly": {
+    en: "English-only model, more accurate for English dictation",
+    fr: "Modele anglais uniquement, plus precis pour la dictee en anglais",
+  },
+
   // Model selector
   "modelSelector.label": { en: "Active model", fr: "Modele actif" },
   "modelSelector.placeholder": {
@@ -1,21 +1,260 @@
 export interface AppConfig {
   hotkey: string;
   hotkey_ptt: string;
+  /** Empty when disabled */
+  hotkey_undo: string;
+  undo_depth: number;
   auto_paste: boolean;
   active_model: string | null;
   language: string;
   audio_device: string | null;
   ui_locale: string;
   first_run_complete: boolean;
+  force_cpu: boolean;
+  watch_folder: WatchFolderConfig;
+  filter: FilterConfig;
+  voice_commands: boolean;
+  normalization: NormalizationConfig;
+  redaction: RedactionConfig;
+  rewrite: RewriteConfig;
+  hook: HookConfig;
+  /** Keys pressed to paste, by physical position on a US layout, e.g. "Ctrl+Shift+V" */
+  paste_chord: string;
+  clipboard_restore: ClipboardRestoreConfig;
+  output_mode: OutputMode;
+  input_backend: InputBackend;
+  sinks: OutputSink[];
+  typing: TypingConfig;
+  continuation: ContinuationConfig;
+  /** Overrides for the application focused when a dictation starts */
+  profiles: AppProfile[];
 }
 
+/** Put back what the user had copied once a dictation has been pasted. */
+export interface ClipboardRestoreConfig {
+  enabled: boolean;
+  delay_ms: number;
+}
+
+/** "paste" goes through the clipboard, "type" sends keystrokes. */
+export type OutputMode = "paste" | "type" | "sinks_only";
+
+export type SinkTarget =
+  | { kind: "file"; path: string }
+  | { kind: "named_pipe"; path: string }
+  | { kind: "webhook"; url: string; allow_remote: boolean }
+  | { kind: "primary_selection" };
+
+/**
+ * `format` placeholders: {text}, {timestamp}, {date}, {time}, {language},
+ * {model}, {window}. File paths accept them too.
+ */
+export type OutputSink = SinkTarget & {
+  name: string;
+  enabled: boolean;
+  format: string | null;
+};
+
+export interface SinkError {
+  sink: string;
+  message: string;
+}
+
+/** "native" is enigo: XTest on X11, system APIs on macOS and Windows. */
+export type InputBackend = "auto" | "native" | "wtype" | "ydotool" | "uinput";
+
+export interface InjectionStatus {
+  session: "x11" | "wayland" | "other";
+  backends: { backend: InputBackend; available: boolean }[];
+}
+
+export interface TypingConfig {
+  /** Pause after each character */
+  char_delay_ms: number;
+  /** Characters typed per main-thread dispatch */
+  chunk_size: number;
+}
+
+/** Joining of a dictation to the previous one, in the same window. */
+export interface ContinuationConfig {
+  enabled: boolean;
+  window_secs: number;
+  lowercase_first_word: boolean;
+}
+
+/** Settings applied when the focused application matches; null keeps the global value. */
+export interface AppProfile {
+  name: string;
+  enabled: boolean;
+  /** X11 WM_CLASS class or instance names, case-insensitive */
+  window_classes: string[];
+  language: string | null;
+  model: string | null;
+  voice_commands: boolean | null;
+  /** Rule list from the text rules run instead of the shared and language lists */
+  text_rules: string | null;
+  auto_paste: boolean | null;
+  paste_chord: string | null;
+  output_mode: OutputMode | null;
+  /** Names of the sinks used for this application */
+  sinks: string[] | null;
+}
+
+export interface ActiveWindow {
+  id: number;
+  class: string;
+  instance: string;
+}
+
+/**
+ * External executable each dictation is piped through (stdin -> stdout).
+ * It receives LOCALWHISPER_LANGUAGE, LOCALWHISPER_MODEL and
+ * LOCALWHISPER_DURATION_MS in its environment.
+ */
+export interface HookConfig {
+  enabled: boolean;
+  command: string;
+  args: string[];
+  timeout_secs: number;
+}
+
+/** Rewrite of each dictation by a local OpenAI-compatible LLM server. */
+export interface RewriteConfig {
+  enabled: boolean;
+  /** Chat-completions URL */
+  endpoint: string;
+  model: string;
+  system_prompt: string;
+  timeout_secs: number;
+  /** Accept endpoints outside this machine */
+  allow_remote: boolean;
+}
+
+/** Spoken numbers rewritten in written form, per category. */
+export interface NormalizationConfig {
+  enabled: boolean;
+  numbers: boolean;
+  ordinals: boolean;
+  dates: boolean;
+  times: boolean;
+  currencies: boolean;
+  percentages: boolean;
+}
+
+export interface FilterConfig {
+  enabled: boolean;
+  silence_rms_threshold: number;
+  drop_phantom_phrases: boolean;
+  collapse_repetitions: boolean;
+}
+
+export type RedactionAction = "off" | "mask" | "placeholder" | "warn";
+
+export interface RedactionPattern {
+  /** Reported in warnings and used for the placeholder ([NAME]) */
+  name: string;
+  pattern: string;
+  case_insensitive: boolean;
+  action: RedactionAction;
+}
+
+/** Personal data detected in a dictation before it reaches the clipboard. */
+export interface RedactionConfig {
+  enabled: boolean;
+  emails: RedactionAction;
+  phones: RedactionAction;
+  ibans: RedactionAction;
+  credit_cards: RedactionAction;
+  custom: RedactionPattern[];
+}
+
+/** Payload of the redaction-warning event. */
+export interface RedactionWarning {
+  /** email, phone, iban, credit_card, or a custom pattern name */
+  kind: string;
+  value: string;
+}
+
+/** Phantom phrases per language code ("*" applies to all languages). */
+export type PhantomPhrases = Record<string, string[]>;
+
+export interface Snippet {
+  id: string;
+  /** Spoken phrase that inserts the snippet */
+  trigger: string;
+  /** Template: {date}, {time}, {datetime}, {weekday}, {clipboard}, {date:%d/%m/%Y} */
+  text: string;
+}
+
+export interface VoiceCommand {
+  phrase: string;
+  output: string;
+  /** Which neighbouring words the output sticks to */
+  attach: "none" | "previous" | "next" | "both";
+  /** Only applies after the grammar's trigger word */
+  explicit: boolean;
+}
+
+export interface VoiceCommandGrammar {
+  /** Word that makes the following command literal */
+  escape: string;
+  /** Word that makes the following phrase a command, explicit ones included */
+  trigger: string;
+  commands: VoiceCommand[];
+}
+
+/** Spoken punctuation grammars keyed by language code. */
+export type VoiceCommandGrammars = Record<string, VoiceCommandGrammar>;
+
+export type TextCase = "lower" | "upper" | "title" | "sentence";
+
+export type TextTransform =
+  | {
+      type: "regex";
+      pattern: string;
+      replacement: string;
+      case_insensitive: boolean;
+    }
+  | { type: "capitalize_first" }
+  | { type: "remove_trailing_period" }
+  | { type: "normalize_whitespace" }
+  | { type: "case"; case: TextCase };
+
+export type TextRule = TextTransform & { enabled: boolean };
+
+/** Ordered rule lists keyed by language code, "*" applies to all. */
+export type TextRuleSets = Record<string, TextRule[]>;
+
+export interface WatchFolderConfig {
+  enabled: boolean;
+  path: string | null;
+  output_format: ExportFormat;
+  move_to_done: boolean;
+}
+
+export type RecommendationReason =
+  | { kind: "benchmark"; real_time_factor: number }
+  | { kind: "benchmark_too_slow"; real_time_factor: number }
+  | { kind: "available_memory"; available_gb: number; total_gb: number }
+  | { kind: "cpu"; cores: number; simd: string[] }
+  | { kind: "gpu"; backend: string; device: string }
+  | { kind: "no_gpu"; backend: string }
+  | { kind: "limited_by_memory" }
+  | { kind: "limited_by_compute" }
+  | { kind: "quantized" }
+  | { kind: "english_only" };
+
 export interface SystemInfo {
   total_ram_gb: number;
+  available_ram_gb: number;
   cpu_cores: number;
+  simd: string[];
+  vulkan_device: string | null;
   os: string;
   arch: string;
+  backend: "metal" | "vulkan" | "openblas" | "cpu";
   recommended_model: string;
-  recommended_model_reason: string;
+  recommendation_reasons: RecommendationReason[];
 }
 
 export interface AudioDevice {
@@ -31,9 +270,26 @@ export interface ModelInfo {
   url: string;
   is_english_only: boolean;
   is_quantized: boolean;
+  supports_diarization: boolean;
   is_downloaded: boolean;
 }
 
+export interface BenchmarkResult {
+  model_id: string;
+  load_ms: number;
+  transcribe_ms: number;
+  real_time_factor: number;
+  peak_memory_mb: number;
+  backend: string;
+  measured_at: number;
+}
+
+export interface BenchmarkProgress {
+  model_id: string;
+  index: number;
+  total: number;
+}
+
 export interface DownloadProgress {
   model_id: string;
   downloaded_bytes: number;
@@ -45,3 +301,33 @@ export interface PermissionStatus {
   microphone: boolean;
   accessibility: boolean;
 }
+
+export interface Segment {
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  confidence: number;
+  speaker: number | null;
+}
+
+export interface TranscriptionResult {
+  text: string;
+  segments: Segment[];
+  language: string;
+  duration_ms: number;
+}
+
+export interface FileTranscriptionProgress {
+  path: string;
+  stage: "decoding" | "transcribing" | "done";
+  percent: number;
+}
+
+export type ExportFormat = "srt" | "vtt" | "txt" | "json" | "md";
+
+export interface WatchFolderProgress {
+  path: string;
+  status: "queued" | "processing" | "done" | "failed";
+  pending: number;
+  error: string | null;
+}